@@ -0,0 +1,48 @@
+use macroquad::prelude::*;
+
+use crate::{ EditorState, Level };
+
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+const ZOOM_STEP: f32 = 1.1;
+
+fn apply_zoom(camera: &mut Camera2D, zoom: f32) {
+    camera.zoom = vec2((1.0 / screen_width()) * 2.0 * zoom, (1.0 / screen_height()) * 2.0 * zoom);
+}
+
+pub fn reset_camera(camera: &mut Camera2D, level: &Level, editor_state: &mut EditorState) {
+    editor_state.zoom = 1.0;
+    editor_state.pan_anchor = None;
+    camera.target = vec2(level.width / 2.0, level.height / 2.0);
+    apply_zoom(camera, editor_state.zoom);
+}
+
+/*
+    Middle mouse drag pans the view and Ctrl + wheel zooms around the cursor,
+    so the world point under the cursor stays put in both cases.
+*/
+pub fn update_camera(camera: &mut Camera2D, editor_state: &mut EditorState, mouse_over_ui: bool) {
+    apply_zoom(camera, editor_state.zoom);
+
+    let mouse_pos = mouse_position();
+    let mouse_pos = vec2(mouse_pos.0, mouse_pos.1);
+
+    if is_mouse_button_down(MouseButton::Middle) {
+        if let Some(anchor) = editor_state.pan_anchor {
+            camera.target += camera.screen_to_world(anchor) - camera.screen_to_world(mouse_pos);
+        }
+        editor_state.pan_anchor = Some(mouse_pos);
+    } else {
+        editor_state.pan_anchor = None;
+    }
+
+    let ctrl_down = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+    let mouse_wheel = mouse_wheel().1;
+    if ctrl_down && !mouse_over_ui && mouse_wheel != 0.0 {
+        let before = camera.screen_to_world(mouse_pos);
+        let step = if mouse_wheel > 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+        editor_state.zoom = (editor_state.zoom * step).clamp(MIN_ZOOM, MAX_ZOOM);
+        apply_zoom(camera, editor_state.zoom);
+        camera.target += before - camera.screen_to_world(mouse_pos);
+    }
+}
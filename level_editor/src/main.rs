@@ -1,8 +1,32 @@
-use std::f32::consts::E;
-
 use macroquad::prelude::*;
+use macroquad::ui::root_ui;
 use serde::{ Serialize, Deserialize };
-use serde_json;
+use string_tables::Strings;
+
+use camera::{ reset_camera, update_camera };
+use occupancy::{ cleanup_level, find_conflict, footprint };
+use timeline::{
+    draw_timeline_panel,
+    draw_trajectories,
+    handle_target_pick,
+    update_timeline,
+};
+use tools::{
+    apply_auto_tiling,
+    draw_ground_tool_preview,
+    ground_tile_source,
+    handle_ground_tool,
+    is_ground_tool,
+};
+use strings::load_strings;
+use ui::{ draw_inspector, draw_toasts, draw_toolbar, push_toast, update_toasts, Toast };
+
+mod camera;
+mod occupancy;
+mod strings;
+mod timeline;
+mod tools;
+mod ui;
 
 const GRID_CELL_SIZE: f32 = 32.0;
 const BUIDLING_SIZE: f32 = GRID_CELL_SIZE * 2.0;
@@ -10,12 +34,61 @@ const CANNON_SIZE: f32 = GRID_CELL_SIZE;
 const PLANE_SIZE: f32 = GRID_CELL_SIZE;
 const PLACEMENT_TYPES_NUM: usize = 5;
 const BUILDING_TYPES_NUM: usize = 3;
+const DEFAULT_LEVEL_WIDTH: f32 = 800.0;
+const DEFAULT_LEVEL_HEIGHT: f32 = 600.0;
+const DEFAULT_CANNON_AMMO: u32 = 10;
+const DEFAULT_PLANE_SPEED: f32 = 1.0;
+
+const PLACEMENTS: [Placement; PLACEMENT_TYPES_NUM] = [
+    Placement::Building,
+    Placement::Cannon,
+    Placement::Plane,
+    Placement::EnemyMissile,
+    Placement::Ground,
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+enum Warhead {
+    #[default]
+    Standard,
+    Mirv,
+}
+
+/*
+    A single enemy missile fired from a spawnpoint, `time` seconds after the
+    wave starts. Speed uses the game's units (pixels per tick).
+*/
+#[derive(Serialize, Deserialize, Clone)]
+struct Launch {
+    time: f32,
+    target_x: f32,
+    target_y: f32,
+    speed: f32,
+    #[serde(default)]
+    warhead: Warhead,
+}
 
 #[derive(Serialize, Deserialize)]
 struct Entity {
     x: f32,
     y: f32,
+    #[serde(default)]
     id: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ammo: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tile: Option<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    launches: Vec<Launch>,
+}
+
+impl Entity {
+    fn new(x: f32, y: f32, id: u8) -> Entity {
+        Entity { x, y, id, ammo: None, speed: None, tile: None, launches: Vec::new() }
+    }
 }
 
 /*
@@ -24,14 +97,43 @@ struct Entity {
 
 #[derive(Serialize, Deserialize)]
 struct Level {
+    #[serde(default = "default_level_width")]
+    width: f32,
+    #[serde(default = "default_level_height")]
+    height: f32,
     buildings: Vec<Entity>,
     cannons: Vec<Entity>,
     planes: Vec<Entity>,
     enemy_missiles: Vec<Entity>,
     ground: Vec<Entity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+impl Level {
+    fn new() -> Level {
+        Level {
+            width: DEFAULT_LEVEL_WIDTH,
+            height: DEFAULT_LEVEL_HEIGHT,
+            buildings: Vec::new(),
+            cannons: Vec::new(),
+            planes: Vec::new(),
+            enemy_missiles: Vec::new(),
+            ground: Vec::new(),
+            seed: None,
+        }
+    }
+}
+
+fn default_level_width() -> f32 {
+    DEFAULT_LEVEL_WIDTH
 }
 
-#[derive(PartialEq)]
+fn default_level_height() -> f32 {
+    DEFAULT_LEVEL_HEIGHT
+}
+
+#[derive(PartialEq, Clone, Copy)]
 enum Placement {
     Building,
     Cannon,
@@ -40,10 +142,70 @@ enum Placement {
     Ground,
 }
 
+impl Placement {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Placement::Building => "placement_label.building",
+            Placement::Cannon => "placement_label.cannon",
+            Placement::Plane => "placement_label.plane",
+            Placement::EnemyMissile => "placement_label.enemy_missile",
+            Placement::Ground => "placement_label.ground",
+        }
+    }
+
+    fn string_key(&self) -> &'static str {
+        match self {
+            Placement::Building => "placement.building",
+            Placement::Cannon => "placement.cannon",
+            Placement::Plane => "placement.plane",
+            Placement::EnemyMissile => "placement.enemy_missile",
+            Placement::Ground => "placement.ground",
+        }
+    }
+
+    fn size(&self) -> f32 {
+        match self {
+            Placement::Building => BUIDLING_SIZE,
+            Placement::Cannon => CANNON_SIZE,
+            Placement::Plane => PLANE_SIZE,
+            Placement::EnemyMissile | Placement::Ground => GRID_CELL_SIZE,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Tool {
+    Place,
+    Select,
+    Brush,
+    Rectangle,
+    Line,
+    Fill,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+struct Selection {
+    placement: Placement,
+    index: usize,
+}
+
 struct EditorState {
+    current_tool: Tool,
     current_placement: Placement,
     current_placement_index: usize,
     current_building_index: usize,
+    selection: Option<Selection>,
+    toasts: Vec<Toast>,
+    strings: Strings,
+    zoom: f32,
+    pan_anchor: Option<Vec2>,
+    auto_tile: bool,
+    stroke_start: Option<(i32, i32)>,
+    stroke_last: Option<(i32, i32)>,
+    stroke_paint: bool,
+    timeline_time: f32,
+    timeline_playing: bool,
+    picking_target: Option<usize>,
 }
 
 fn draw_building(x: f32, y: f32, texture: &Texture2D, color: Color) {
@@ -62,29 +224,26 @@ fn window_conf() -> Conf {
     }
 }
 
-fn draw_editor_grid() {
-    let screen_width = screen_width();
-    let screen_height = screen_height();
-
-    let grid_width = (screen_width / GRID_CELL_SIZE).ceil() as u16;
-    let grid_height = (screen_height / GRID_CELL_SIZE).ceil() as u16;
+fn draw_editor_grid(level: &Level) {
+    let grid_width = (level.width / GRID_CELL_SIZE).ceil() as u16;
+    let grid_height = (level.height / GRID_CELL_SIZE).ceil() as u16;
 
-    for x in 0..grid_width {
+    for x in 0..=grid_width {
         draw_line(
             (x as f32) * GRID_CELL_SIZE,
             0.0,
             (x as f32) * GRID_CELL_SIZE,
-            screen_height,
+            level.height,
             1.0,
             DARKGRAY
         );
     }
 
-    for y in 0..grid_height {
+    for y in 0..=grid_height {
         draw_line(
             0.0,
             (y as f32) * GRID_CELL_SIZE,
-            screen_width,
+            level.width,
             (y as f32) * GRID_CELL_SIZE,
             1.0,
             DARKGRAY
@@ -92,12 +251,25 @@ fn draw_editor_grid() {
     }
 }
 
+fn draw_canvas_boundary(level: &Level) {
+    draw_rectangle_lines(0.0, 0.0, level.width, level.height, 4.0, ORANGE);
+}
+
 fn draw_pointer(
     cam: &Camera2D,
+    level: &Level,
     editor_state: &EditorState,
-    building_textures: &Vec<Texture2D>,
+    building_textures: &[Texture2D],
     ground_texture: &Texture2D
 ) {
+    if is_ground_tool(editor_state.current_tool) {
+        draw_ground_tool_preview(cam, editor_state, ground_texture);
+        return;
+    }
+    if editor_state.current_tool != Tool::Place {
+        return;
+    }
+
     let mouse_pos = mouse_position();
     let mouse_pos = vec2(mouse_pos.0, mouse_pos.1);
     let mouse_pos = cam.screen_to_world(mouse_pos);
@@ -120,9 +292,15 @@ fn draw_pointer(
             draw_enemy_missile(x, y);
         }
         Placement::Ground => {
-            draw_ground(x, y, &ground_texture, Color::from_rgba(255, 255, 255, 100));
+            draw_ground(x, y, ground_texture, None, Color::from_rgba(255, 255, 255, 100));
         }
     }
+
+    if find_conflict(level, editor_state.current_placement, x, y).is_some() {
+        let area = footprint(editor_state.current_placement, x, y);
+        draw_rectangle(area.x, area.y, area.w, area.h, Color::from_rgba(255, 0, 0, 90));
+        draw_rectangle_lines(area.x, area.y, area.w, area.h, 2.0, RED);
+    }
 }
 
 fn draw_cannon(x: f32, y: f32) {
@@ -140,7 +318,12 @@ fn draw_enemy_missile(x: f32, y: f32) {
     draw_text("M", x + GRID_CELL_SIZE / 2.0, y + GRID_CELL_SIZE / 2.0, 16.0, WHITE);
 }
 
-fn draw_level(level: &Level, building_textures: &Vec<Texture2D>, ground_texture: &Texture2D) {
+fn draw_level(
+    level: &Level,
+    building_textures: &[Texture2D],
+    ground_texture: &Texture2D,
+    ground_tiles_texture: &Texture2D
+) {
     for building in &level.buildings {
         draw_building(building.x, building.y, &building_textures[building.id as usize], WHITE);
     }
@@ -158,25 +341,52 @@ fn draw_level(level: &Level, building_textures: &Vec<Texture2D>, ground_texture:
     }
 
     for ground in &level.ground {
-        draw_ground(ground.x, ground.y, &ground_texture, WHITE);
+        match ground.tile {
+            Some(tile) => {
+                draw_ground(
+                    ground.x,
+                    ground.y,
+                    ground_tiles_texture,
+                    Some(ground_tile_source(tile)),
+                    WHITE
+                );
+            }
+            None => {
+                draw_ground(ground.x, ground.y, ground_texture, None, WHITE);
+            }
+        }
+    }
+}
+
+fn draw_selection(level: &Level, editor_state: &EditorState) {
+    if let Some(selection) = editor_state.selection {
+        if let Some(entity) = entities(level, selection.placement).get(selection.index) {
+            let size = selection.placement.size();
+            draw_rectangle_lines(entity.x, entity.y, size, size, 2.0, YELLOW);
+        }
     }
 }
 
-fn draw_background(texture: &Texture2D) {
+fn draw_background(texture: &Texture2D, level: &Level) {
     draw_texture_ex(texture, 0.0, 0.0, WHITE, DrawTextureParams {
-        dest_size: Some(vec2(screen_width(), screen_height())),
+        dest_size: Some(vec2(level.width, level.height)),
         ..Default::default()
     });
 }
 
-fn draw_ground(x: f32, y: f32, texture: &Texture2D, color: Color) {
+fn draw_ground(x: f32, y: f32, texture: &Texture2D, source: Option<Rect>, color: Color) {
     draw_texture_ex(texture, x, y, color, DrawTextureParams {
         dest_size: Some(vec2(GRID_CELL_SIZE, GRID_CELL_SIZE)),
+        source,
         ..Default::default()
     });
 }
 
 fn handle_placement_on_mouse_wheel(editor_state: &mut EditorState) {
+    if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+        return;
+    }
+
     let mouse_wheel = mouse_wheel().1;
     let treshold = 0.1;
 
@@ -196,6 +406,7 @@ fn handle_placement_on_mouse_wheel(editor_state: &mut EditorState) {
 }
 
 fn set_placement_by_index(editor_state: &mut EditorState) {
+    editor_state.current_tool = Tool::Place;
     match editor_state.current_placement_index {
         0 => {
             editor_state.current_placement = Placement::Building;
@@ -229,30 +440,44 @@ fn get_entity_xy_from_mouse(cam: &Camera2D) -> (f32, f32) {
 
 fn place_building(cam: &Camera2D, level: &mut Level, editor_state: &EditorState) {
     let (x, y) = get_entity_xy_from_mouse(cam);
-    level.buildings.push(Entity { x, y, id: editor_state.current_building_index as u8 });
+    level.buildings.push(Entity::new(x, y, editor_state.current_building_index as u8));
 }
 
 fn place_cannon(cam: &Camera2D, level: &mut Level) {
     let (x, y) = get_entity_xy_from_mouse(cam);
-    level.cannons.push(Entity { x, y, id: 1 });
+    level.cannons.push(Entity::new(x, y, 1));
 }
 
 fn place_plane(cam: &Camera2D, level: &mut Level) {
     let (x, y) = get_entity_xy_from_mouse(cam);
-    level.planes.push(Entity { x, y, id: 1 });
+    level.planes.push(Entity::new(x, y, 1));
 }
 
 fn place_enemy_missile(cam: &Camera2D, level: &mut Level) {
     let (x, y) = get_entity_xy_from_mouse(cam);
-    level.enemy_missiles.push(Entity { x, y, id: 1 });
+    level.enemy_missiles.push(Entity::new(x, y, 1));
 }
 
 fn place_ground(cam: &Camera2D, level: &mut Level) {
     let (x, y) = get_entity_xy_from_mouse(cam);
-    level.ground.push(Entity { x, y, id: 1 });
+    level.ground.push(Entity::new(x, y, 1));
 }
 
-fn place_entity(cam: &Camera2D, level: &mut Level, editor_state: &EditorState) {
+/*
+    Returns true when an entity was placed.
+*/
+fn place_entity(cam: &Camera2D, level: &mut Level, editor_state: &mut EditorState) -> bool {
+    let (x, y) = get_entity_xy_from_mouse(cam);
+    if let Some(conflict) = find_conflict(level, editor_state.current_placement, x, y) {
+        let strings = &editor_state.strings;
+        let message = strings.format("toast.cannot_place", &[
+            &strings.get(editor_state.current_placement.string_key()),
+            &strings.get(conflict.string_key()),
+        ]);
+        push_toast(&mut editor_state.toasts, &message, RED);
+        return false;
+    }
+
     match editor_state.current_placement {
         Placement::Building => {
             place_building(cam, level, editor_state);
@@ -270,6 +495,50 @@ fn place_entity(cam: &Camera2D, level: &mut Level, editor_state: &EditorState) {
             place_ground(cam, level);
         }
     }
+
+    true
+}
+
+fn entities(level: &Level, placement: Placement) -> &Vec<Entity> {
+    match placement {
+        Placement::Building => &level.buildings,
+        Placement::Cannon => &level.cannons,
+        Placement::Plane => &level.planes,
+        Placement::EnemyMissile => &level.enemy_missiles,
+        Placement::Ground => &level.ground,
+    }
+}
+
+fn entities_mut(level: &mut Level, placement: Placement) -> &mut Vec<Entity> {
+    match placement {
+        Placement::Building => &mut level.buildings,
+        Placement::Cannon => &mut level.cannons,
+        Placement::Plane => &mut level.planes,
+        Placement::EnemyMissile => &mut level.enemy_missiles,
+        Placement::Ground => &mut level.ground,
+    }
+}
+
+/*
+    Picks the topmost entity under the cursor, checking placement types
+    in reverse draw order so that what is visible on top wins.
+*/
+fn select_entity(cam: &Camera2D, level: &Level, editor_state: &mut EditorState) {
+    let mouse_pos = mouse_position();
+    let mouse_pos = cam.screen_to_world(vec2(mouse_pos.0, mouse_pos.1));
+
+    editor_state.selection = None;
+    editor_state.picking_target = None;
+    for placement in PLACEMENTS.iter().rev() {
+        let size = placement.size();
+        let hit = entities(level, *placement)
+            .iter()
+            .rposition(|entity| { Rect::new(entity.x, entity.y, size, size).contains(mouse_pos) });
+        if let Some(index) = hit {
+            editor_state.selection = Some(Selection { placement: *placement, index });
+            return;
+        }
+    }
 }
 
 fn remove_entity_from_cell(cam: &Camera2D, entities: &mut Vec<Entity>) {
@@ -297,50 +566,54 @@ fn remove_entity(cam: &Camera2D, level: &mut Level, editor_state: &EditorState)
     }
 }
 
-fn save_level(level: &Level) {
+fn load_level(toasts: &mut Vec<Toast>, strings: &Strings) -> Option<Level> {
+    let level_json = std::fs::read_to_string("level.json").ok()?;
+    match serde_json::from_str(&level_json) {
+        Ok(level) => {
+            push_toast(toasts, &strings.get("toast.level_loaded"), GREEN);
+            Some(level)
+        }
+        Err(err) => {
+            push_toast(toasts, &strings.format("toast.load_failed", &[&err]), RED);
+            None
+        }
+    }
+}
+
+fn clean_up_level(level: &mut Level, editor_state: &mut EditorState) {
+    let (removed, conflicts) = cleanup_level(level);
+    editor_state.selection = None;
+    editor_state.picking_target = None;
+    let strings = &editor_state.strings;
+    push_toast(&mut editor_state.toasts, &strings.format("toast.duplicates_removed", &[&removed]), GREEN);
+    if conflicts > 0 {
+        push_toast(&mut editor_state.toasts, &strings.format("toast.overlaps_left", &[&conflicts]), ORANGE);
+    }
+}
+
+fn save_level(level: &Level, toasts: &mut Vec<Toast>, strings: &Strings) {
     let level_json = serde_json::to_string(level);
     match level_json {
         Ok(level_json) => {
             match std::fs::write("level.json", level_json) {
                 Ok(_) => {
-                    draw_text(
-                        "Level saved!",
-                        screen_width() / 2.0,
-                        screen_height() / 2.0,
-                        72.0,
-                        GREEN
-                    );
+                    push_toast(toasts, &strings.get("toast.level_saved"), GREEN);
                 }
-                Err(_) => {
-                    draw_text(
-                        "Failed to save level!",
-                        screen_width() / 2.0,
-                        screen_height() / 2.0,
-                        72.0,
-                        RED
-                    );
+                Err(err) => {
+                    push_toast(toasts, &strings.format("toast.save_failed", &[&err]), RED);
                 }
             }
         }
-        Err(_) => {
-            draw_text(
-                "Failed to save level!",
-                screen_width() / 2.0,
-                screen_height() / 2.0,
-                72.0,
-                RED
-            );
+        Err(err) => {
+            push_toast(toasts, &strings.format("toast.save_failed", &[&err]), RED);
         }
     }
 }
 
 fn handle_change_entity_type(editor_state: &mut EditorState) {
-    match editor_state.current_placement {
-        Placement::Building => {
-            editor_state.current_building_index += 1;
-            editor_state.current_building_index %= BUILDING_TYPES_NUM;
-        }
-        _ => {}
+    if editor_state.current_placement == Placement::Building {
+        editor_state.current_building_index += 1;
+        editor_state.current_building_index %= BUILDING_TYPES_NUM;
     }
 }
 
@@ -352,56 +625,123 @@ async fn main() {
     let missile_texture = load_texture("assets/missile.png").await.unwrap();
     let background_texture = load_texture("assets/background.png").await.unwrap();
     let ground_texture = load_texture("assets/ground.png").await.unwrap();
+    let ground_tiles_texture = load_texture("assets/ground_tiles.png").await.unwrap();
     building1_texture.set_filter(FilterMode::Nearest);
     building2_texture.set_filter(FilterMode::Nearest);
     building3_texture.set_filter(FilterMode::Nearest);
-    let building_textures = vec![building1_texture, building2_texture, building3_texture];
+    let building_textures = [building1_texture, building2_texture, building3_texture];
     missile_texture.set_filter(FilterMode::Nearest);
     background_texture.set_filter(FilterMode::Nearest);
     ground_texture.set_filter(FilterMode::Nearest);
+    ground_tiles_texture.set_filter(FilterMode::Nearest);
 
-    let mut level = Level {
-        buildings: Vec::new(),
-        cannons: Vec::new(),
-        planes: Vec::new(),
-        enemy_missiles: Vec::new(),
-        ground: Vec::new(),
-    };
+    let toast_font = load_ttf_font_from_bytes(include_bytes!("../../assets/fonts/CommanderPixel.ttf")).unwrap();
+
+    let strings = load_strings();
+    let mut toasts = Vec::new();
+    let mut level = load_level(&mut toasts, &strings).unwrap_or_else(Level::new);
 
     let mut editor_state = EditorState {
+        current_tool: Tool::Place,
         current_placement: Placement::Building,
         current_placement_index: 0,
         current_building_index: 0,
+        selection: None,
+        toasts,
+        strings,
+        zoom: 1.0,
+        pan_anchor: None,
+        auto_tile: false,
+        stroke_start: None,
+        stroke_last: None,
+        stroke_paint: true,
+        timeline_time: 0.0,
+        timeline_playing: false,
+        picking_target: None,
     };
 
-    let camera = Camera2D {
-        zoom: vec2((1.0 / screen_width()) * 2.0, (1.0 / screen_height()) * 2.0),
-        target: vec2(screen_width() / 2.0, screen_height() / 2.0),
-        ..Default::default()
-    };
-    set_camera(&camera);
+    let mut camera = Camera2D::default();
+    reset_camera(&mut camera, &level, &mut editor_state);
 
     loop {
-        if is_mouse_button_pressed(MouseButton::Left) {
-            place_entity(&camera, &mut level, &editor_state);
+        let mouse_pos = mouse_position();
+        let mouse_over_ui = root_ui().is_mouse_over(vec2(mouse_pos.0, mouse_pos.1));
+
+        if is_key_pressed(KeyCode::Home) {
+            reset_camera(&mut camera, &level, &mut editor_state);
         }
+        update_camera(&mut camera, &mut editor_state, mouse_over_ui);
+        set_camera(&camera);
+
+        let mut ground_changed = false;
+        if is_ground_tool(editor_state.current_tool) {
+            ground_changed = handle_ground_tool(&camera, &mut level, &mut editor_state, mouse_over_ui);
+        } else if !mouse_over_ui && !handle_target_pick(&camera, &mut level, &mut editor_state) {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                match editor_state.current_tool {
+                    Tool::Select => {
+                        select_entity(&camera, &level, &mut editor_state);
+                    }
+                    _ => {
+                        ground_changed =
+                            place_entity(&camera, &mut level, &mut editor_state) &&
+                            editor_state.current_placement == Placement::Ground;
+                    }
+                }
+            }
 
-        if is_mouse_button_pressed(MouseButton::Right) {
-            remove_entity(&camera, &mut level, &editor_state);
+            if is_mouse_button_pressed(MouseButton::Right) {
+                remove_entity(&camera, &mut level, &editor_state);
+                editor_state.selection = None;
+                ground_changed = editor_state.current_placement == Placement::Ground;
+            }
+        }
+        if !mouse_over_ui {
+            handle_placement_on_mouse_wheel(&mut editor_state);
         }
-        handle_placement_on_mouse_wheel(&mut editor_state);
         if is_key_pressed(KeyCode::Space) {
             handle_change_entity_type(&mut editor_state);
         }
+        if is_key_pressed(KeyCode::F2) {
+            editor_state.strings.next_language();
+            let strings = &editor_state.strings;
+            let message = strings.format("toast.language", &[&strings.language_name()]);
+            push_toast(&mut editor_state.toasts, &message, DARKBLUE);
+        }
 
         clear_background(LIGHTGRAY);
-        draw_background(&background_texture);
-        draw_editor_grid();
-        draw_level(&level, &building_textures, &ground_texture);
-        draw_pointer(&camera, &editor_state, &building_textures, &ground_texture);
+        draw_background(&background_texture, &level);
+        draw_editor_grid(&level);
+        draw_canvas_boundary(&level);
+        draw_level(&level, &building_textures, &ground_texture, &ground_tiles_texture);
+        draw_trajectories(&level, &editor_state);
+        draw_selection(&level, &editor_state);
+        draw_pointer(&camera, &level, &editor_state, &building_textures, &ground_texture);
         if is_key_pressed(KeyCode::S) {
-            save_level(&level);
+            save_level(&level, &mut editor_state.toasts, &editor_state.strings);
+        }
+        if is_key_pressed(KeyCode::L) {
+            if let Some(loaded) = load_level(&mut editor_state.toasts, &editor_state.strings) {
+                level = loaded;
+                editor_state.selection = None;
+                editor_state.picking_target = None;
+            }
+        }
+
+        set_default_camera();
+        if draw_toolbar(&mut level, &mut editor_state) {
+            ground_changed = true;
+        }
+        if ground_changed {
+            apply_auto_tiling(&mut level, editor_state.auto_tile);
+        }
+        if draw_inspector(&mut level, &mut editor_state) {
+            apply_auto_tiling(&mut level, editor_state.auto_tile);
         }
+        draw_timeline_panel(&mut level, &mut editor_state);
+        update_timeline(&level, &mut editor_state, get_frame_time());
+        update_toasts(&mut editor_state.toasts, get_frame_time());
+        draw_toasts(&editor_state.toasts, &toast_font);
 
         next_frame().await;
     }
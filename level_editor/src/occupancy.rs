@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::{ entities, entities_mut, Entity, Level, Placement, GRID_CELL_SIZE, PLACEMENTS };
+
+/*
+    Every entity covers a square footprint starting at its top-left cell,
+    so a building takes 2x2 cells and everything else a single one.
+*/
+pub fn footprint(placement: Placement, x: f32, y: f32) -> Rect {
+    Rect::new(x, y, placement.size(), placement.size())
+}
+
+/*
+    Footprints that only touch along an edge do not count as overlapping.
+*/
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+}
+
+/*
+    Nothing may stack on an entity of its own type. Buildings, cannons and
+    ground are solid and also block each other, while planes and enemy
+    missile spawnpoints live in the sky and only block their own kind.
+*/
+fn blocks(a: Placement, b: Placement) -> bool {
+    let solid = |placement: Placement| {
+        matches!(placement, Placement::Building | Placement::Cannon | Placement::Ground)
+    };
+
+    a == b || (solid(a) && solid(b))
+}
+
+/*
+    Grid cells a footprint reaches into, also for entities moved off the grid.
+*/
+fn cells_under(area: Rect) -> impl Iterator<Item = (i32, i32)> {
+    let first_x = (area.x / GRID_CELL_SIZE).floor() as i32;
+    let first_y = (area.y / GRID_CELL_SIZE).floor() as i32;
+    let last_x = ((area.x + area.w) / GRID_CELL_SIZE).ceil() as i32 - 1;
+    let last_y = ((area.y + area.h) / GRID_CELL_SIZE).ceil() as i32 - 1;
+
+    (first_x..=last_x).flat_map(move |x| (first_y..=last_y).map(move |y| (x, y)))
+}
+
+/*
+    Footprints of everything that blocks one placement type, bucketed by grid
+    cell. Tools that place many entities at once build it once and then check
+    every cell without going through the whole level again.
+*/
+pub struct Occupancy {
+    placement: Placement,
+    cells: HashMap<(i32, i32), Vec<(Placement, Rect)>>,
+}
+
+impl Occupancy {
+    pub fn new(level: &Level, placement: Placement) -> Occupancy {
+        let mut occupancy = Occupancy { placement, cells: HashMap::new() };
+        for other in PLACEMENTS {
+            for entity in entities(level, other) {
+                occupancy.insert(other, entity.x, entity.y);
+            }
+        }
+
+        occupancy
+    }
+
+    pub fn insert(&mut self, placement: Placement, x: f32, y: f32) {
+        if !blocks(self.placement, placement) {
+            return;
+        }
+        let area = footprint(placement, x, y);
+        for cell in cells_under(area) {
+            self.cells.entry(cell).or_default().push((placement, area));
+        }
+    }
+
+    /*
+        Same answer as `find_conflict` for the level the occupancy was built from.
+    */
+    pub fn conflict(&self, x: f32, y: f32) -> Option<Placement> {
+        let area = footprint(self.placement, x, y);
+        let order = |placement: &Placement| PLACEMENTS.iter().position(|other| other == placement);
+        cells_under(area)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(|(_, other_area)| overlaps(area, *other_area))
+            .map(|(other, _)| *other)
+            .min_by_key(order)
+    }
+}
+
+pub fn find_conflict(level: &Level, placement: Placement, x: f32, y: f32) -> Option<Placement> {
+    let area = footprint(placement, x, y);
+    for other in PLACEMENTS {
+        if !blocks(placement, other) {
+            continue;
+        }
+        let hit = entities(level, other)
+            .iter()
+            .any(|entity| { overlaps(area, footprint(other, entity.x, entity.y)) });
+        if hit {
+            return Some(other);
+        }
+    }
+
+    None
+}
+
+fn count_conflicts(level: &Level) -> usize {
+    let mut conflicts = 0;
+    for (index, placement) in PLACEMENTS.iter().enumerate() {
+        for other in &PLACEMENTS[index + 1..] {
+            if !blocks(*placement, *other) {
+                continue;
+            }
+            for entity in entities(level, *placement) {
+                let area = footprint(*placement, entity.x, entity.y);
+                conflicts += entities(level, *other)
+                    .iter()
+                    .filter(|other_entity| {
+                        overlaps(area, footprint(*other, other_entity.x, other_entity.y))
+                    })
+                    .count();
+            }
+        }
+    }
+
+    conflicts
+}
+
+/*
+    Drops entities stacked on an earlier entity of the same type, which older
+    level files contain. Overlaps between different types are only counted,
+    since picking which of the two to keep is up to the designer.
+    Returns (removed duplicates, remaining conflicts).
+*/
+pub fn cleanup_level(level: &mut Level) -> (usize, usize) {
+    let mut removed = 0;
+    for placement in PLACEMENTS {
+        let entities = entities_mut(level, placement);
+        let mut kept: Vec<Entity> = Vec::new();
+        for entity in entities.drain(..) {
+            let area = footprint(placement, entity.x, entity.y);
+            let duplicate = kept
+                .iter()
+                .any(|other| { overlaps(area, footprint(placement, other.x, other.y)) });
+            if duplicate {
+                removed += 1;
+            } else {
+                kept.push(entity);
+            }
+        }
+        *entities = kept;
+    }
+
+    (removed, count_conflicts(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(x: i32, y: i32) -> (f32, f32) {
+        ((x as f32) * GRID_CELL_SIZE, (y as f32) * GRID_CELL_SIZE)
+    }
+
+    fn level_with_building() -> Level {
+        let mut level = Level::new();
+        let (x, y) = cell(2, 2);
+        level.buildings.push(Entity::new(x, y, 0));
+        level
+    }
+
+    #[test]
+    fn solid_entities_block_each_other_and_the_sky_only_its_own_kind() {
+        let level = level_with_building();
+        let (x, y) = cell(3, 3);
+        assert!(find_conflict(&level, Placement::Ground, x, y) == Some(Placement::Building));
+        assert!(find_conflict(&level, Placement::Cannon, x, y) == Some(Placement::Building));
+        assert!(find_conflict(&level, Placement::Plane, x, y).is_none());
+        assert!(find_conflict(&level, Placement::EnemyMissile, x, y).is_none());
+
+        // touching along an edge is fine
+        let (x, y) = cell(4, 2);
+        assert!(find_conflict(&level, Placement::Building, x, y).is_none());
+        let (x, y) = cell(3, 2);
+        assert!(find_conflict(&level, Placement::Building, x, y) == Some(Placement::Building));
+    }
+
+    #[test]
+    fn occupancy_agrees_with_find_conflict() {
+        let mut level = level_with_building();
+        let (x, y) = cell(5, 5);
+        level.planes.push(Entity::new(x, y, 1));
+        level.ground.push(Entity::new(x + 10.0, y, 1));
+
+        for placement in PLACEMENTS {
+            let occupancy = Occupancy::new(&level, placement);
+            for cell_x in 0..8 {
+                for cell_y in 0..8 {
+                    let (x, y) = cell(cell_x, cell_y);
+                    assert!(
+                        occupancy.conflict(x, y) == find_conflict(&level, placement, x, y),
+                        "{} at {} {}",
+                        placement.label_key(),
+                        cell_x,
+                        cell_y
+                    );
+                }
+            }
+        }
+
+        let mut occupancy = Occupancy::new(&level, Placement::Ground);
+        let (x, y) = cell(0, 0);
+        assert!(occupancy.conflict(x, y).is_none());
+        occupancy.insert(Placement::Ground, x, y);
+        assert!(occupancy.conflict(x, y) == Some(Placement::Ground));
+    }
+
+    #[test]
+    fn cleanup_drops_stacked_duplicates_and_counts_the_rest() {
+        let mut level = level_with_building();
+        let (x, y) = cell(2, 2);
+        level.buildings.push(Entity::new(x, y, 1));
+        level.buildings.push(Entity::new(x + GRID_CELL_SIZE, y, 2));
+        let (x, y) = cell(0, 0);
+        level.ground.push(Entity::new(x, y, 1));
+        level.ground.push(Entity::new(x, y, 1));
+        // a cannon on the first building's roof is left for the designer
+        let (x, y) = cell(2, 3);
+        level.cannons.push(Entity::new(x, y, 1));
+
+        assert_eq!(cleanup_level(&mut level), (3, 1));
+        assert_eq!(level.buildings.len(), 1);
+        assert_eq!(level.buildings[0].id, 0);
+        assert_eq!(level.ground.len(), 1);
+        assert_eq!(level.cannons.len(), 1);
+        assert_eq!(cleanup_level(&mut level), (0, 1));
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use string_tables::{ Strings, Table };
+
+const LANGUAGES_FOLDER: &str = "assets/lang";
+
+fn language_tables() -> BTreeMap<String, Table> {
+    let mut tables = BTreeMap::new();
+    let files = std::fs::read_dir(LANGUAGES_FOLDER).into_iter().flatten().flatten();
+    for file in files {
+        let path = file.path();
+        let code = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(code) if path.extension().is_some_and(|extension| extension == "json") => code,
+            _ => {
+                continue;
+            }
+        };
+        let data = std::fs::read_to_string(&path).unwrap();
+        let table = serde_json::from_str(&data).unwrap_or_else(|error| {
+            panic!("{}: {}", path.display(), error)
+        });
+        tables.insert(code.to_string(), table);
+    }
+    tables
+}
+
+/*
+    Starts in English.
+*/
+pub fn load_strings() -> Strings {
+    Strings::new(language_tables())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use string_tables::FALLBACK_LANGUAGE;
+
+    #[test]
+    fn every_language_has_every_english_string() {
+        let tables = language_tables();
+        let english = &tables[FALLBACK_LANGUAGE];
+        for (code, table) in &tables {
+            for key in english.keys() {
+                assert!(table.contains_key(key), "{} is missing {}", code, key);
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use macroquad::prelude::*;
+use macroquad::ui::{ hash, root_ui, widgets::Window };
+
+use crate::{ EditorState, Entity, Launch, Level, Placement, Warhead, GRID_CELL_SIZE };
+
+const TIMELINE_WIDTH: f32 = 320.0;
+const TIMELINE_TOP: f32 = 230.0;
+const TIMELINE_HEIGHT: f32 = 360.0;
+const TRACK_HEIGHT: f32 = 24.0;
+const MAX_LAUNCH_TIME: f32 = 600.0;
+const MAX_LAUNCH_SPEED: f32 = 10.0;
+const DEFAULT_LAUNCH_SPEED: f32 = 1.0;
+const TICKS_PER_SECOND: f32 = 60.0;
+const MIRV_SPREAD: f32 = 96.0;
+const DASH_LENGTH: f32 = 8.0;
+const WARHEAD_KEYS: [&str; 2] = ["warhead.standard", "warhead.mirv"];
+
+fn launch_origin(spawnpoint: &Entity) -> Vec2 {
+    vec2(spawnpoint.x + GRID_CELL_SIZE / 2.0, spawnpoint.y + GRID_CELL_SIZE / 2.0)
+}
+
+fn launch_target(launch: &Launch) -> Vec2 {
+    vec2(launch.target_x, launch.target_y)
+}
+
+fn flight_time(from: Vec2, to: Vec2, speed: f32) -> f32 {
+    if speed <= 0.0 {
+        return 0.0;
+    }
+
+    from.distance(to) / (speed * TICKS_PER_SECOND)
+}
+
+/*
+    The path a launch follows as (start, end, start time) segments. A MIRV
+    flies half way as one warhead and then splits into three that spread
+    around the target, the same way the game does it.
+*/
+fn launch_segments(spawnpoint: &Entity, launch: &Launch) -> Vec<(Vec2, Vec2, f32)> {
+    let origin = launch_origin(spawnpoint);
+    let target = launch_target(launch);
+    match launch.warhead {
+        Warhead::Standard => vec![(origin, target, launch.time)],
+        Warhead::Mirv => {
+            let split = origin.lerp(target, 0.5);
+            let split_time = launch.time + flight_time(origin, split, launch.speed);
+            vec![
+                (origin, split, launch.time),
+                (split, target, split_time),
+                (split, target - vec2(MIRV_SPREAD, 0.0), split_time),
+                (split, target + vec2(MIRV_SPREAD, 0.0), split_time)
+            ]
+        }
+    }
+}
+
+/*
+    The time at which the last warhead of the wave lands.
+*/
+pub fn wave_duration(level: &Level) -> f32 {
+    let mut duration: f32 = 0.0;
+    for spawnpoint in &level.enemy_missiles {
+        for launch in &spawnpoint.launches {
+            for (start, end, time) in launch_segments(spawnpoint, launch) {
+                duration = duration.max(time + flight_time(start, end, launch.speed));
+            }
+        }
+    }
+
+    duration
+}
+
+pub fn update_timeline(level: &Level, editor_state: &mut EditorState, delta: f32) {
+    if !editor_state.timeline_playing {
+        return;
+    }
+
+    editor_state.timeline_time += delta;
+    if editor_state.timeline_time > wave_duration(level) {
+        editor_state.timeline_time = 0.0;
+    }
+}
+
+fn draw_dashed_line(start: Vec2, end: Vec2, thickness: f32, color: Color) {
+    let length = start.distance(end);
+    if length <= 0.0 {
+        return;
+    }
+
+    let direction = (end - start) / length;
+    let mut distance = 0.0;
+    while distance < length {
+        let dash_end = (distance + DASH_LENGTH).min(length);
+        let a = start + direction * distance;
+        let b = start + direction * dash_end;
+        draw_line(a.x, a.y, b.x, b.y, thickness, color);
+        distance += DASH_LENGTH * 2.0;
+    }
+}
+
+fn warhead_color(warhead: Warhead) -> Color {
+    match warhead {
+        Warhead::Standard => RED,
+        Warhead::Mirv => MAGENTA,
+    }
+}
+
+pub fn draw_trajectories(level: &Level, editor_state: &EditorState) {
+    let time = editor_state.timeline_time;
+    for spawnpoint in &level.enemy_missiles {
+        for launch in &spawnpoint.launches {
+            let color = warhead_color(launch.warhead);
+            for (start, end, start_time) in launch_segments(spawnpoint, launch) {
+                draw_dashed_line(start, end, 1.0, color);
+
+                let duration = flight_time(start, end, launch.speed);
+                if time >= start_time && time < start_time + duration {
+                    let position = start.lerp(end, (time - start_time) / duration);
+                    draw_circle(position.x, position.y, 4.0, color);
+                }
+            }
+
+            let target = launch_target(launch);
+            draw_line(target.x - 5.0, target.y - 5.0, target.x + 5.0, target.y + 5.0, 1.0, color);
+            draw_line(target.x + 5.0, target.y - 5.0, target.x - 5.0, target.y + 5.0, 1.0, color);
+        }
+    }
+}
+
+/*
+    While a target is being picked the next left click on the canvas moves
+    that launch's target. Returns true when the click was consumed.
+*/
+pub fn handle_target_pick(cam: &Camera2D, level: &mut Level, editor_state: &mut EditorState) -> bool {
+    let (selection, launch_index) = match (editor_state.selection, editor_state.picking_target) {
+        (Some(selection), Some(launch_index)) => (selection, launch_index),
+        _ => {
+            return false;
+        }
+    };
+    if !is_mouse_button_pressed(MouseButton::Left) {
+        return false;
+    }
+
+    editor_state.picking_target = None;
+    if selection.placement != Placement::EnemyMissile {
+        return false;
+    }
+    let mouse_pos = mouse_position();
+    let mouse_pos = cam.screen_to_world(vec2(mouse_pos.0, mouse_pos.1));
+    if let Some(spawnpoint) = level.enemy_missiles.get_mut(selection.index) {
+        if let Some(launch) = spawnpoint.launches.get_mut(launch_index) {
+            launch.target_x = mouse_pos.x;
+            launch.target_y = mouse_pos.y;
+        }
+    }
+
+    true
+}
+
+pub fn draw_timeline_panel(level: &mut Level, editor_state: &mut EditorState) {
+    let duration = wave_duration(level);
+    let spawnpoint_index = editor_state.selection
+        .filter(|selection| selection.placement == Placement::EnemyMissile)
+        .map(|selection| selection.index)
+        .filter(|index| *index < level.enemy_missiles.len());
+    let canvas_height = level.height;
+    let strings = &editor_state.strings;
+    let warhead_names: Vec<String> = WARHEAD_KEYS.iter().map(|key| { strings.get(key) }).collect();
+    let warhead_names: Vec<&str> = warhead_names.iter().map(|name| { name.as_str() }).collect();
+
+    let position = vec2(screen_width() - TIMELINE_WIDTH - 10.0, TIMELINE_TOP);
+    Window::new(hash!(), position, vec2(TIMELINE_WIDTH, TIMELINE_HEIGHT))
+        .label(&strings.get("timeline.title"))
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            let play_key = if editor_state.timeline_playing { "timeline.pause" } else { "timeline.play" };
+            if ui.button(None, strings.get(play_key).as_str()) {
+                editor_state.timeline_playing = !editor_state.timeline_playing;
+            }
+            ui.slider(hash!(), &strings.get("timeline.time"), 0.0..duration.max(1.0), &mut editor_state.timeline_time);
+
+            let mut canvas = ui.canvas();
+            let width = TIMELINE_WIDTH - 20.0;
+            let track = canvas.request_space(vec2(width, TRACK_HEIGHT));
+            canvas.rect(Rect::new(track.x, track.y, width, TRACK_HEIGHT), GRAY, DARKGRAY);
+            let scale = width / duration.max(1.0);
+            for (index, spawnpoint) in level.enemy_missiles.iter().enumerate() {
+                let color = if Some(index) == spawnpoint_index { YELLOW } else { RED };
+                for launch in &spawnpoint.launches {
+                    let x = track.x + launch.time * scale;
+                    canvas.line(vec2(x, track.y + 2.0), vec2(x, track.y + TRACK_HEIGHT - 2.0), color);
+                }
+            }
+            let playhead = track.x + editor_state.timeline_time.min(duration.max(1.0)) * scale;
+            canvas.line(vec2(playhead, track.y), vec2(playhead, track.y + TRACK_HEIGHT), WHITE);
+            ui.separator();
+
+            let spawnpoint_index = match spawnpoint_index {
+                Some(index) => index,
+                None => {
+                    ui.label(None, &strings.get("timeline.select_spawnpoint"));
+                    ui.label(None, &strings.get("timeline.select_spawnpoint_2"));
+                    return;
+                }
+            };
+
+            let spawnpoint = &mut level.enemy_missiles[spawnpoint_index];
+            let origin = launch_origin(spawnpoint);
+            ui.label(None, &strings.format("timeline.spawnpoint", &[&spawnpoint_index]));
+            if ui.button(None, strings.get("timeline.add_launch").as_str()) {
+                let time = spawnpoint.launches
+                    .last()
+                    .map(|launch| launch.time + 1.0)
+                    .unwrap_or(editor_state.timeline_time);
+                spawnpoint.launches.push(Launch {
+                    time,
+                    target_x: origin.x,
+                    target_y: canvas_height - GRID_CELL_SIZE,
+                    speed: DEFAULT_LAUNCH_SPEED,
+                    warhead: Warhead::Standard,
+                });
+            }
+
+            let mut removed = None;
+            for (index, launch) in spawnpoint.launches.iter_mut().enumerate() {
+                let label = strings.format("timeline.launch", &[&(index + 1), &format!("{:.1}", launch.time)]);
+                ui.tree_node(hash!("launch", spawnpoint_index, index), &label, |ui| {
+                    ui.drag(hash!("time", spawnpoint_index, index), &strings.get("timeline.time"), (0.0, MAX_LAUNCH_TIME), &mut launch.time);
+                    ui.drag(hash!("target_x", spawnpoint_index, index), &strings.get("timeline.target_x"), None, &mut launch.target_x);
+                    ui.drag(hash!("target_y", spawnpoint_index, index), &strings.get("timeline.target_y"), None, &mut launch.target_y);
+                    ui.drag(hash!("speed", spawnpoint_index, index), &strings.get("timeline.speed"), (0.1, MAX_LAUNCH_SPEED), &mut launch.speed);
+
+                    let mut warhead = launch.warhead as usize;
+                    ui.combo_box(hash!("warhead", spawnpoint_index, index), &strings.get("timeline.warhead"), &warhead_names, &mut warhead);
+                    launch.warhead = if warhead == 1 { Warhead::Mirv } else { Warhead::Standard };
+
+                    let picking = editor_state.picking_target == Some(index);
+                    let pick_key = if picking { "timeline.picking_target" } else { "timeline.pick_target" };
+                    if ui.button(None, strings.get(pick_key).as_str()) {
+                        editor_state.picking_target = Some(index);
+                    }
+                    if ui.button(None, strings.get("timeline.remove").as_str()) {
+                        removed = Some(index);
+                    }
+                });
+            }
+
+            if let Some(index) = removed {
+                spawnpoint.launches.remove(index);
+                editor_state.picking_target = None;
+            }
+        });
+}
@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+
+use macroquad::prelude::*;
+
+use crate::occupancy::Occupancy;
+use crate::{ draw_ground, EditorState, Entity, Level, Placement, Tool, GRID_CELL_SIZE };
+
+const GROUND_TILES_PER_ROW: u8 = 4;
+const GROUND_TILE_SIZE: f32 = 8.0;
+const NEIGHBOUR_NORTH: u8 = 1;
+const NEIGHBOUR_EAST: u8 = 2;
+const NEIGHBOUR_SOUTH: u8 = 4;
+const NEIGHBOUR_WEST: u8 = 8;
+
+type Cell = (i32, i32);
+
+fn cell_from_mouse(cam: &Camera2D) -> Cell {
+    let mouse_pos = mouse_position();
+    let mouse_pos = cam.screen_to_world(vec2(mouse_pos.0, mouse_pos.1));
+
+    ((mouse_pos.x / GRID_CELL_SIZE).floor() as i32, (mouse_pos.y / GRID_CELL_SIZE).floor() as i32)
+}
+
+fn cell_from_entity(entity: &Entity) -> Cell {
+    ((entity.x / GRID_CELL_SIZE).floor() as i32, (entity.y / GRID_CELL_SIZE).floor() as i32)
+}
+
+fn cell_in_canvas(level: &Level, cell: Cell) -> bool {
+    let columns = (level.width / GRID_CELL_SIZE).ceil() as i32;
+    let rows = (level.height / GRID_CELL_SIZE).ceil() as i32;
+
+    cell.0 >= 0 && cell.1 >= 0 && cell.0 < columns && cell.1 < rows
+}
+
+fn ground_cells(level: &Level) -> HashSet<Cell> {
+    level.ground.iter().map(cell_from_entity).collect()
+}
+
+fn can_place_ground(level: &Level, occupancy: &Occupancy, cell: Cell) -> bool {
+    let x = (cell.0 as f32) * GRID_CELL_SIZE;
+    let y = (cell.1 as f32) * GRID_CELL_SIZE;
+
+    cell_in_canvas(level, cell) && occupancy.conflict(x, y).is_none()
+}
+
+/*
+    Returns true when anything was added.
+*/
+fn add_ground(level: &mut Level, cells: &[Cell]) -> bool {
+    let mut occupancy = Occupancy::new(level, Placement::Ground);
+    let mut changed = false;
+    for cell in cells {
+        if !can_place_ground(level, &occupancy, *cell) {
+            continue;
+        }
+        let x = (cell.0 as f32) * GRID_CELL_SIZE;
+        let y = (cell.1 as f32) * GRID_CELL_SIZE;
+        level.ground.push(Entity::new(x, y, 1));
+        occupancy.insert(Placement::Ground, x, y);
+        changed = true;
+    }
+
+    changed
+}
+
+fn erase_ground(level: &mut Level, cells: &[Cell]) -> bool {
+    let cells: HashSet<Cell> = cells.iter().copied().collect();
+    let count = level.ground.len();
+    level.ground.retain(|entity| { !cells.contains(&cell_from_entity(entity)) });
+
+    count != level.ground.len()
+}
+
+fn rectangle_cells(start: Cell, end: Cell) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    for x in start.0.min(end.0)..=start.0.max(end.0) {
+        for y in start.1.min(end.1)..=start.1.max(end.1) {
+            cells.push((x, y));
+        }
+    }
+
+    cells
+}
+
+fn line_cells(start: Cell, end: Cell) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let (mut x, mut y) = start;
+    let dx = (end.0 - start.0).abs();
+    let dy = -(end.1 - start.1).abs();
+    let step_x = if start.0 < end.0 { 1 } else { -1 };
+    let step_y = if start.1 < end.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        cells.push((x, y));
+        if x == end.0 && y == end.1 {
+            break;
+        }
+        let doubled = error * 2;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+
+    cells
+}
+
+/*
+    Collects the 4-connected region of cells that share the start cell's state,
+    limited to the canvas: either ground, or free cells where ground can go.
+*/
+fn flood_cells(level: &Level, start: Cell) -> Vec<Cell> {
+    if !cell_in_canvas(level, start) {
+        return Vec::new();
+    }
+
+    let occupied = ground_cells(level);
+    let occupancy = Occupancy::new(level, Placement::Ground);
+    let filled = occupied.contains(&start);
+    let matches = |cell: Cell| {
+        if filled { occupied.contains(&cell) } else { can_place_ground(level, &occupancy, cell) }
+    };
+    let mut visited = HashSet::new();
+    let mut stack = vec![start];
+    let mut cells = Vec::new();
+
+    while let Some(cell) = stack.pop() {
+        if
+            !cell_in_canvas(level, cell) ||
+            !matches(cell) ||
+            !visited.insert(cell)
+        {
+            continue;
+        }
+        cells.push(cell);
+        stack.push((cell.0 + 1, cell.1));
+        stack.push((cell.0 - 1, cell.1));
+        stack.push((cell.0, cell.1 + 1));
+        stack.push((cell.0, cell.1 - 1));
+    }
+
+    cells
+}
+
+/*
+    With auto-tiling off the variants are dropped and plain ground is drawn.
+*/
+pub fn apply_auto_tiling(level: &mut Level, enabled: bool) {
+    let occupied = ground_cells(level);
+    for entity in &mut level.ground {
+        if !enabled {
+            entity.tile = None;
+            continue;
+        }
+
+        let (x, y) = cell_from_entity(entity);
+        let mut mask = 0;
+        if occupied.contains(&(x, y - 1)) {
+            mask |= NEIGHBOUR_NORTH;
+        }
+        if occupied.contains(&(x + 1, y)) {
+            mask |= NEIGHBOUR_EAST;
+        }
+        if occupied.contains(&(x, y + 1)) {
+            mask |= NEIGHBOUR_SOUTH;
+        }
+        if occupied.contains(&(x - 1, y)) {
+            mask |= NEIGHBOUR_WEST;
+        }
+        entity.tile = Some(mask);
+    }
+}
+
+pub fn ground_tile_source(tile: u8) -> Rect {
+    Rect::new(
+        ((tile % GROUND_TILES_PER_ROW) as f32) * GROUND_TILE_SIZE,
+        ((tile / GROUND_TILES_PER_ROW) as f32) * GROUND_TILE_SIZE,
+        GROUND_TILE_SIZE,
+        GROUND_TILE_SIZE
+    )
+}
+
+pub fn is_ground_tool(tool: Tool) -> bool {
+    matches!(tool, Tool::Brush | Tool::Rectangle | Tool::Line | Tool::Fill)
+}
+
+/*
+    Left button paints ground and right button erases it. Brush works while the
+    button is held, rectangle and line commit on release, fill acts on press.
+    Returns true when the ground layer changed.
+*/
+pub fn handle_ground_tool(cam: &Camera2D, level: &mut Level, editor_state: &mut EditorState, mouse_over_ui: bool) -> bool {
+    let cell = cell_from_mouse(cam);
+    let mut changed = false;
+
+    for (button, paint) in [(MouseButton::Left, true), (MouseButton::Right, false)] {
+        if is_mouse_button_pressed(button) && !mouse_over_ui {
+            editor_state.stroke_start = Some(cell);
+            editor_state.stroke_last = Some(cell);
+            editor_state.stroke_paint = paint;
+        }
+    }
+
+    // the last cell is None once a fill has run, until the button is pressed again
+    let (start, last) = match editor_state.stroke_start {
+        Some(start) => (start, editor_state.stroke_last),
+        None => {
+            return false;
+        }
+    };
+    let button = if editor_state.stroke_paint { MouseButton::Left } else { MouseButton::Right };
+    let released = is_mouse_button_released(button) || !is_mouse_button_down(button);
+
+    let cells = match editor_state.current_tool {
+        Tool::Brush => line_cells(last.unwrap_or(start), cell),
+        Tool::Rectangle if released => rectangle_cells(start, cell),
+        Tool::Line if released => line_cells(start, cell),
+        Tool::Fill if last.is_some() => flood_cells(level, start),
+        _ => Vec::new(),
+    };
+    if !cells.is_empty() {
+        changed = if editor_state.stroke_paint {
+            add_ground(level, &cells)
+        } else {
+            erase_ground(level, &cells)
+        };
+    }
+
+    editor_state.stroke_last = if editor_state.current_tool == Tool::Fill { None } else { Some(cell) };
+    if released {
+        editor_state.stroke_start = None;
+        editor_state.stroke_last = None;
+    }
+
+    changed
+}
+
+pub fn draw_ground_tool_preview(cam: &Camera2D, editor_state: &EditorState, ground_texture: &Texture2D) {
+    let cell = cell_from_mouse(cam);
+    let cells = match (editor_state.current_tool, editor_state.stroke_start) {
+        (Tool::Rectangle, Some(start)) => rectangle_cells(start, cell),
+        (Tool::Line, Some(start)) => line_cells(start, cell),
+        _ => vec![cell],
+    };
+
+    let color = if editor_state.stroke_start.is_some() && !editor_state.stroke_paint {
+        Color::from_rgba(255, 80, 80, 120)
+    } else {
+        Color::from_rgba(255, 255, 255, 100)
+    };
+    for (x, y) in cells {
+        draw_ground(
+            (x as f32) * GRID_CELL_SIZE,
+            (y as f32) * GRID_CELL_SIZE,
+            ground_texture,
+            None,
+            color
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ground_at(level: &mut Level, cells: &[Cell]) {
+        for (x, y) in cells {
+            level.ground.push(Entity::new((*x as f32) * GRID_CELL_SIZE, (*y as f32) * GRID_CELL_SIZE, 1));
+        }
+    }
+
+    fn tile_at(level: &Level, cell: Cell) -> Option<u8> {
+        level.ground
+            .iter()
+            .find(|entity| cell_from_entity(entity) == cell)
+            .and_then(|entity| entity.tile)
+    }
+
+    #[test]
+    fn lines_include_both_ends_in_any_direction() {
+        for (start, end) in [((0, 0), (5, 2)), ((5, 2), (0, 0)), ((3, -4), (1, 6)), ((2, 2), (2, 2))] {
+            let cells = line_cells(start, end);
+            assert_eq!(cells.first(), Some(&start));
+            assert_eq!(cells.last(), Some(&end));
+            let dx = (end.0 - start.0).abs();
+            let dy = (end.1 - start.1).abs();
+            assert_eq!(cells.len() as i32, dx.max(dy) + 1);
+            for pair in cells.windows(2) {
+                assert!((pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn flood_fill_stays_inside_the_canvas_and_its_region() {
+        let mut level = Level::new();
+        level.width = GRID_CELL_SIZE * 4.0;
+        level.height = GRID_CELL_SIZE * 3.0;
+        assert_eq!(flood_cells(&level, (0, 0)).len(), 12);
+        assert!(flood_cells(&level, (-1, 0)).is_empty());
+        assert!(flood_cells(&level, (4, 0)).is_empty());
+
+        // a wall of ground across column 1 splits the free cells in two
+        ground_at(&mut level, &[(1, 0), (1, 1), (1, 2)]);
+        let mut free = flood_cells(&level, (0, 1));
+        free.sort();
+        assert_eq!(free, vec![(0, 0), (0, 1), (0, 2)]);
+        let mut wall = flood_cells(&level, (1, 0));
+        wall.sort();
+        assert_eq!(wall, vec![(1, 0), (1, 1), (1, 2)]);
+
+        // buildings block ground, so the fill goes around them
+        level.buildings.push(Entity::new(GRID_CELL_SIZE * 2.0, 0.0, 0));
+        assert_eq!(flood_cells(&level, (3, 2)).len(), 2);
+    }
+
+    #[test]
+    fn auto_tiling_picks_a_variant_from_the_four_neighbours() {
+        let mut level = Level::new();
+        // a plus sign and a lone cell
+        ground_at(&mut level, &[(5, 4), (5, 3), (6, 4), (5, 5), (4, 4), (10, 10)]);
+        apply_auto_tiling(&mut level, true);
+
+        let all = NEIGHBOUR_NORTH | NEIGHBOUR_EAST | NEIGHBOUR_SOUTH | NEIGHBOUR_WEST;
+        assert_eq!(tile_at(&level, (5, 4)), Some(all));
+        assert_eq!(tile_at(&level, (5, 3)), Some(NEIGHBOUR_SOUTH));
+        assert_eq!(tile_at(&level, (6, 4)), Some(NEIGHBOUR_WEST));
+        assert_eq!(tile_at(&level, (5, 5)), Some(NEIGHBOUR_NORTH));
+        assert_eq!(tile_at(&level, (4, 4)), Some(NEIGHBOUR_EAST));
+        assert_eq!(tile_at(&level, (10, 10)), Some(0));
+
+        apply_auto_tiling(&mut level, false);
+        assert!(level.ground.iter().all(|entity| entity.tile.is_none()));
+    }
+}
@@ -0,0 +1,262 @@
+use macroquad::prelude::*;
+use macroquad::ui::{ hash, root_ui, widgets::{ Button, Window } };
+
+use crate::{
+    clean_up_level,
+    entities_mut,
+    find_conflict,
+    set_placement_by_index,
+    EditorState,
+    Level,
+    Placement,
+    Tool,
+    BUILDING_TYPES_NUM,
+    DEFAULT_CANNON_AMMO,
+    DEFAULT_PLANE_SPEED,
+    GRID_CELL_SIZE,
+    PLACEMENTS,
+};
+
+const TOOLBAR_WIDTH: f32 = 150.0;
+const TOOLBAR_HEIGHT: f32 = 590.0;
+const MAX_CANVAS_SIZE: f32 = 4096.0;
+const INSPECTOR_WIDTH: f32 = 220.0;
+const INSPECTOR_HEIGHT: f32 = 210.0;
+const GROUND_TOOLS: [(Tool, &str); 4] = [
+    (Tool::Brush, "tools.brush"),
+    (Tool::Rectangle, "tools.rectangle"),
+    (Tool::Line, "tools.line"),
+    (Tool::Fill, "tools.fill"),
+];
+const TOAST_DURATION: f32 = 2.5;
+const TOAST_FADE_TIME: f32 = 0.5;
+const TOAST_FONT_SIZE: f32 = 30.0;
+
+/*
+    Repeating the newest message keeps it up instead of stacking copies.
+*/
+pub struct Toast {
+    text: String,
+    color: Color,
+    time_left: f32,
+}
+
+pub fn push_toast(toasts: &mut Vec<Toast>, text: &str, color: Color) {
+    if let Some(last) = toasts.last_mut() {
+        if last.text == text {
+            last.time_left = TOAST_DURATION;
+            return;
+        }
+    }
+    toasts.push(Toast { text: text.to_string(), color, time_left: TOAST_DURATION });
+}
+
+pub fn update_toasts(toasts: &mut Vec<Toast>, delta: f32) {
+    for toast in toasts.iter_mut() {
+        toast.time_left -= delta;
+    }
+    toasts.retain(|toast| { toast.time_left > 0.0 });
+}
+
+pub fn draw_toasts(toasts: &[Toast], font: &Font) {
+    let mut y = screen_height() - 20.0;
+    for toast in toasts.iter().rev() {
+        let alpha = (toast.time_left / TOAST_FADE_TIME).min(1.0);
+        let size = measure_text(&toast.text, Some(font), TOAST_FONT_SIZE as u16, 1.0);
+        let x = screen_width() / 2.0 - size.width / 2.0;
+        draw_rectangle(
+            x - 8.0,
+            y - size.height - 8.0,
+            size.width + 16.0,
+            size.height + 16.0,
+            Color::new(0.0, 0.0, 0.0, 0.6 * alpha)
+        );
+        let mut color = toast.color;
+        color.a = alpha;
+        draw_text_ex(&toast.text, x, y, TextParams {
+            font: Some(font),
+            font_size: TOAST_FONT_SIZE as u16,
+            color,
+            ..Default::default()
+        });
+        y -= size.height + 24.0;
+    }
+}
+
+fn tool_label(name: &str, active: bool) -> String {
+    if active { format!("> {}", name) } else { name.to_string() }
+}
+
+/*
+    Returns true when the auto-tiling option was toggled and the ground needs retiling.
+*/
+pub fn draw_toolbar(level: &mut Level, editor_state: &mut EditorState) -> bool {
+    let auto_tile = editor_state.auto_tile;
+    /* Looked up up front, the window borrows the whole editor state for its buttons. */
+    let strings = &editor_state.strings;
+    let placement_labels: Vec<String> = PLACEMENTS.iter().map(|placement| { strings.get(placement.label_key()) }).collect();
+    let building_labels: Vec<String> = (0..BUILDING_TYPES_NUM)
+        .map(|building_index| { strings.format("tools.building_type", &[&(building_index + 1)]) })
+        .collect();
+    let ground_labels: Vec<String> = GROUND_TOOLS.iter().map(|(_, key)| { strings.get(key) }).collect();
+    let title = strings.get("tools.title");
+    let select_label = strings.get("tools.select");
+    let ground_label = strings.get("tools.ground");
+    let auto_tile_label = strings.get("tools.auto_tile");
+    let canvas_label = strings.get("tools.canvas");
+    let width_label = strings.get("tools.width");
+    let height_label = strings.get("tools.height");
+    let clean_up_label = strings.get("tools.clean_up");
+
+    Window::new(hash!(), vec2(10.0, 10.0), vec2(TOOLBAR_WIDTH, TOOLBAR_HEIGHT))
+        .label(&title)
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            let select_active = editor_state.current_tool == Tool::Select;
+            if Button::new(tool_label(&select_label, select_active)).selected(select_active).ui(ui) {
+                editor_state.current_tool = Tool::Select;
+            }
+            ui.separator();
+
+            for (index, placement) in PLACEMENTS.iter().enumerate() {
+                let active =
+                    editor_state.current_tool == Tool::Place &&
+                    editor_state.current_placement == *placement;
+                if Button::new(tool_label(&placement_labels[index], active)).selected(active).ui(ui) {
+                    editor_state.current_placement_index = index;
+                    set_placement_by_index(editor_state);
+                }
+            }
+            ui.separator();
+
+            for (building_index, label) in building_labels.iter().enumerate() {
+                let active = editor_state.current_building_index == building_index;
+                if Button::new(tool_label(label, active)).selected(active).ui(ui) {
+                    editor_state.current_building_index = building_index;
+                    editor_state.current_placement_index = 0;
+                    set_placement_by_index(editor_state);
+                }
+            }
+            ui.separator();
+
+            ui.label(None, &ground_label);
+            for ((tool, _), label) in GROUND_TOOLS.iter().zip(&ground_labels) {
+                let active = editor_state.current_tool == *tool;
+                if Button::new(tool_label(label, active)).selected(active).ui(ui) {
+                    editor_state.current_tool = *tool;
+                }
+            }
+            ui.checkbox(hash!(), &auto_tile_label, &mut editor_state.auto_tile);
+            ui.separator();
+
+            ui.label(None, &canvas_label);
+            ui.drag(hash!(), &width_label, (GRID_CELL_SIZE, MAX_CANVAS_SIZE), &mut level.width);
+            ui.drag(hash!(), &height_label, (GRID_CELL_SIZE, MAX_CANVAS_SIZE), &mut level.height);
+            level.width = ((level.width / GRID_CELL_SIZE).round() * GRID_CELL_SIZE).max(GRID_CELL_SIZE);
+            level.height = ((level.height / GRID_CELL_SIZE).round() * GRID_CELL_SIZE).max(GRID_CELL_SIZE);
+            ui.separator();
+
+            if ui.button(None, clean_up_label.as_str()) {
+                clean_up_level(level, editor_state);
+            }
+        });
+
+    auto_tile != editor_state.auto_tile
+}
+
+/*
+    Moves made with the x/y fields follow the same occupancy rules as placing.
+    Returns true when ground was moved or deleted, so the tiles need redoing.
+*/
+pub fn draw_inspector(level: &mut Level, editor_state: &mut EditorState) -> bool {
+    let selection = match editor_state.selection {
+        Some(selection) => selection,
+        None => {
+            return false;
+        }
+    };
+
+    let entities = entities_mut(level, selection.placement);
+    if selection.index >= entities.len() {
+        editor_state.selection = None;
+        return false;
+    }
+    let old_position = (entities[selection.index].x, entities[selection.index].y);
+
+    let mut delete = false;
+    let strings = &editor_state.strings;
+    let position = vec2(screen_width() - INSPECTOR_WIDTH - 10.0, 10.0);
+    Window::new(hash!(), position, vec2(INSPECTOR_WIDTH, INSPECTOR_HEIGHT))
+        .label(&strings.get("inspector.title"))
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            let entity = &mut entities[selection.index];
+            let name = strings.get(selection.placement.label_key());
+            ui.label(None, &strings.format("inspector.entity", &[&name, &selection.index]));
+            ui.drag(hash!(), "x", None, &mut entity.x);
+            ui.drag(hash!(), "y", None, &mut entity.y);
+
+            let mut id = entity.id as u32;
+            match selection.placement {
+                Placement::Building => {
+                    ui.drag(hash!(), &strings.get("inspector.id"), (0, (BUILDING_TYPES_NUM - 1) as u32), &mut id);
+                }
+                _ => {
+                    ui.drag(hash!(), &strings.get("inspector.id"), (0, u8::MAX as u32), &mut id);
+                }
+            }
+            entity.id = id.min(u8::MAX as u32) as u8;
+
+            match selection.placement {
+                Placement::Cannon => {
+                    let mut ammo = entity.ammo.unwrap_or(DEFAULT_CANNON_AMMO);
+                    ui.drag(hash!(), &strings.get("inspector.ammo"), None, &mut ammo);
+                    if ammo != entity.ammo.unwrap_or(DEFAULT_CANNON_AMMO) {
+                        entity.ammo = Some(ammo);
+                    }
+                }
+                Placement::Plane => {
+                    let mut speed = entity.speed.unwrap_or(DEFAULT_PLANE_SPEED);
+                    ui.drag(hash!(), &strings.get("inspector.speed"), (0.0, 10.0), &mut speed);
+                    if speed != entity.speed.unwrap_or(DEFAULT_PLANE_SPEED) {
+                        entity.speed = Some(speed);
+                    }
+                }
+                _ => {}
+            }
+
+            ui.separator();
+            if ui.button(None, strings.get("inspector.delete").as_str()) {
+                delete = true;
+            }
+        });
+
+    if delete {
+        entities.remove(selection.index);
+        editor_state.selection = None;
+        return selection.placement == Placement::Ground;
+    }
+
+    let entity = entities.remove(selection.index);
+    let moved = (entity.x, entity.y) != old_position;
+    let conflict = if moved {
+        find_conflict(level, selection.placement, entity.x, entity.y)
+    } else {
+        None
+    };
+    let entities = entities_mut(level, selection.placement);
+    entities.insert(selection.index, entity);
+    if let Some(conflict) = conflict {
+        let entity = &mut entities[selection.index];
+        (entity.x, entity.y) = old_position;
+        let strings = &editor_state.strings;
+        let message = strings.format("toast.cannot_move", &[
+            &strings.get(selection.placement.string_key()),
+            &strings.get(conflict.string_key()),
+        ]);
+        push_toast(&mut editor_state.toasts, &message, RED);
+        return false;
+    }
+
+    moved && selection.placement == Placement::Ground
+}
@@ -0,0 +1,205 @@
+use crate::data::Assets;
+use macroquad::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub const PLANE_PROPELLER: &str = "plane_propeller";
+pub const LAUNCHER_FIRE: &str = "launcher_fire";
+pub const CITY_DESTROYED: &str = "city_destroyed";
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    Loop,
+    /// Plays once and holds the last frame.
+    Once,
+    PingPong,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// `[x, y, width, height]` of the frame in the texture, in texels. The
+    /// whole texture when left out.
+    #[serde(default)]
+    pub source: Option<[f32; 4]>,
+    pub ticks: u32,
+    /// Moves the sprite, in level units along the sprite's own axes so that
+    /// it turns with the sprite.
+    #[serde(default)]
+    pub offset: [f32; 2],
+    /// Multiplied with the sprite's colour.
+    #[serde(default)]
+    pub tint: Option<[f32; 4]>,
+}
+
+impl Frame {
+    /// How sprites without an animation are drawn.
+    pub const STILL: Frame = Frame {
+        source: None,
+        ticks: 1,
+        offset: [0.0, 0.0],
+        tint: None,
+    };
+
+    pub fn source_rect(&self) -> Option<Rect> {
+        self.source.map(|[x, y, w, h]| Rect::new(x, y, w, h))
+    }
+
+    pub fn tinted(&self, color: Color) -> Color {
+        match self.tint {
+            Some([r, g, b, a]) => Color::new(color.r * r, color.g * g, color.b * b, color.a * a),
+            None => color,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub mode: LoopMode,
+    pub frames: Vec<Frame>,
+}
+
+impl Animation {
+    pub fn duration(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.ticks.max(1)).sum()
+    }
+
+    pub fn frame_at(&self, tick: u32) -> &Frame {
+        if self.frames.is_empty() {
+            return &Frame::STILL;
+        }
+        let duration = self.duration();
+
+        let mut tick = match self.mode {
+            LoopMode::Loop => tick % duration,
+            LoopMode::Once => tick.min(duration - 1),
+            LoopMode::PingPong => {
+                // the end frames are not shown twice at the turns
+                let first = self.frames[0].ticks.max(1);
+                let last = self.frames[self.frames.len() - 1].ticks.max(1);
+                let period = (duration * 2).saturating_sub(first + last).max(duration);
+                let tick = tick % period;
+                if tick < duration {
+                    tick
+                } else {
+                    duration - last - 1 - (tick - duration)
+                }
+            }
+        };
+        for frame in &self.frames {
+            let ticks = frame.ticks.max(1);
+            if tick < ticks {
+                return frame;
+            }
+            tick -= ticks;
+        }
+        &self.frames[self.frames.len() - 1]
+    }
+}
+
+/// Which animation an entity plays and how far in it is. Entities keep only
+/// this, the frames are looked up in [`Animations`] when drawing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationState {
+    pub name: &'static str,
+    pub tick: u32,
+}
+
+impl AnimationState {
+    pub fn new(name: &'static str) -> AnimationState {
+        AnimationState { name, tick: 0 }
+    }
+
+    /// Already played through, for one-shot animations that wait to be
+    /// restarted.
+    pub fn done(name: &'static str) -> AnimationState {
+        AnimationState {
+            name,
+            tick: u32::MAX,
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.tick = self.tick.saturating_add(1);
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Animations(HashMap<String, Animation>);
+
+impl Animations {
+    pub fn get(&self, name: &str) -> Option<&Animation> {
+        self.0.get(name)
+    }
+
+    /// Frame to draw for `state`. Unknown animations draw the still sprite.
+    pub fn frame(&self, state: Option<&AnimationState>) -> &Frame {
+        state
+            .and_then(|state| Some(self.get(state.name)?.frame_at(state.tick)))
+            .unwrap_or(&Frame::STILL)
+    }
+
+    pub fn duration(&self, name: &str) -> u32 {
+        self.get(name).map_or(0, Animation::duration)
+    }
+}
+
+pub fn load_animations_from_file(path: &str) -> Animations {
+    let data = Assets::get(path).unwrap().data;
+    serde_json::from_slice(&data).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(mode: LoopMode) -> Animation {
+        let frame = |x: f32, ticks| Frame {
+            source: Some([x, 0.0, 10.0, 10.0]),
+            ticks,
+            ..Frame::STILL
+        };
+        Animation {
+            mode,
+            frames: vec![frame(0.0, 2), frame(10.0, 1), frame(20.0, 2)],
+        }
+    }
+
+    fn frames(animation: &Animation, ticks: u32) -> Vec<f32> {
+        (0..ticks)
+            .map(|tick| animation.frame_at(tick).source.unwrap()[0] / 10.0)
+            .collect()
+    }
+
+    #[test]
+    fn loop_modes_pick_frames_by_tick() {
+        assert_eq!(
+            frames(&animation(LoopMode::Loop), 7),
+            [0.0, 0.0, 1.0, 2.0, 2.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            frames(&animation(LoopMode::Once), 7),
+            [0.0, 0.0, 1.0, 2.0, 2.0, 2.0, 2.0]
+        );
+        assert_eq!(
+            frames(&animation(LoopMode::PingPong), 10),
+            [0.0, 0.0, 1.0, 2.0, 2.0, 1.0, 0.0, 0.0, 1.0, 2.0]
+        );
+        assert_eq!(
+            animation(LoopMode::Once).frame_at(u32::MAX).source.unwrap()[0],
+            20.0
+        );
+    }
+
+    #[test]
+    fn embedded_animations_load() {
+        let animations = load_animations_from_file("animations.json");
+        for name in [PLANE_PROPELLER, LAUNCHER_FIRE, CITY_DESTROYED] {
+            assert!(animations.duration(name) > 0, "{} is missing", name);
+        }
+        assert_eq!(
+            animations.frame(Some(&AnimationState::new("missing"))),
+            &Frame::STILL
+        );
+    }
+}
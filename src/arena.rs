@@ -0,0 +1,231 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Handle to a value stored in an [`Arena`].
+///
+/// Slots are reused once their value is removed, but every reuse bumps the
+/// slot's generation, so an old handle never resolves to the new value.
+pub struct Id<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+// implemented by hand, deriving would require `T` itself to implement them
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Id({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Slotmap-style storage with stable, generational handles. Removing a value
+/// does not move any other value, unlike `Vec::retain`.
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena {
+            slots: vec![],
+            free: vec![],
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> Id<T> {
+        let index = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.generation += 1;
+                slot.value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        Id {
+            index,
+            generation: self.slots[index as usize].generation,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn remove(&mut self, id: Id<T>) -> Option<T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+        self.free.push(id.index);
+        Some(value)
+    }
+
+    pub fn get(&self, id: Id<T>) -> Option<&T> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: Id<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| {
+                (
+                    Id {
+                        index: index as u32,
+                        generation: slot.generation,
+                        marker: PhantomData,
+                    },
+                    value,
+                )
+            })
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id<T>, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let generation = slot.generation;
+                slot.value.as_mut().map(|value| {
+                    (
+                        Id {
+                            index: index as u32,
+                            generation,
+                            marker: PhantomData,
+                        },
+                        value,
+                    )
+                })
+            })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+    }
+
+    /// The values that are kept keep their ids.
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.as_ref().is_some_and(|value| !keep(value)) {
+                slot.value = None;
+                self.free.push(index as u32);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_survive_removal_of_other_values() {
+        let mut arena = Arena::new();
+        let first = arena.insert("first");
+        let second = arena.insert("second");
+        let third = arena.insert("third");
+
+        arena.retain(|value| *value != "first");
+        assert_eq!(arena.get(first), None);
+        assert_eq!(arena.get(second), Some(&"second"));
+        assert_eq!(arena.get(third), Some(&"third"));
+        assert_eq!(arena.values().count(), 2);
+    }
+
+    #[test]
+    fn stale_ids_do_not_resolve_to_reused_slots() {
+        let mut arena = Arena::new();
+        let old = arena.insert(1);
+        assert_eq!(arena.remove(old), Some(1));
+
+        let new = arena.insert(2);
+        assert_ne!(old, new);
+        assert_eq!(arena.get(old), None);
+        assert_eq!(arena.remove(old), None);
+        assert_eq!(arena.get(new), Some(&2));
+    }
+
+    #[test]
+    fn emptying_the_arena_invalidates_every_id() {
+        let mut arena = Arena::new();
+        let ids: Vec<_> = (0..4).map(|value| arena.insert(value)).collect();
+        arena.retain(|_| false);
+        assert_eq!(arena.values().next(), None);
+
+        for value in 10..14 {
+            arena.insert(value);
+        }
+        assert!(ids.iter().all(|id| arena.get(*id).is_none()));
+        assert_eq!(arena.values().sum::<i32>(), 46);
+    }
+}
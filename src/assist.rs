@@ -0,0 +1,276 @@
+use crate::collision::{collider_shape, sweep_intersects, Shape};
+use crate::data::{Game, CANNON_BASE_WIDTH, MISSILE_RADIUS, PLAYER_MISSILE_SPEED};
+use crate::ecs::{Components, Team, World};
+use crate::input::ControlScheme;
+use crate::logic::{entity_shape, flight_ticks, get_closest_launcher, launcher_ids};
+use macroquad::prelude::*;
+
+/// Where an enemy missile is heading, if nothing stops it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    pub position: Vec2,
+    /// Where it detonates, or leaves the level for missiles without a target.
+    pub end: Vec2,
+    /// Where it will be when the quickest interceptor fired now reaches the
+    /// aim point.
+    pub intercept: Option<Vec2>,
+}
+
+/// How soon the first enemy missile on its way to a city gets there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threat {
+    /// Top centre of the city.
+    pub position: Vec2,
+    pub ticks: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shot {
+    pub origin: Vec2,
+    pub aim: Vec2,
+    /// How close an interceptor has to pass to hit something. Interceptors
+    /// hit on contact, there is no blast radius.
+    pub radius: f32,
+    pub ticks: u32,
+}
+
+/// Only drawn, it never changes how a run plays out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AimAssist {
+    pub projections: Vec<Projection>,
+    pub threats: Vec<Threat>,
+    pub shots: Vec<Shot>,
+}
+
+impl AimAssist {
+    pub fn new(game: &Game, controls: ControlScheme) -> AimAssist {
+        let world = &game.world;
+        let level = Rect::new(0.0, 0.0, game.level_size.x, game.level_size.y);
+        let shots = shots(world, game.aim, controls);
+        let quickest = shots.iter().map(|shot| shot.ticks).min();
+        let enemies: Vec<&Components> = world
+            .values()
+            .filter(|entity| entity.missile.is_some() && entity.team == Some(Team::Enemy))
+            .filter(|entity| entity.is_alive())
+            .collect();
+
+        let projections = enemies
+            .iter()
+            .filter_map(|enemy| {
+                let position = enemy.position?.0;
+                let ticks = ticks_left(enemy, level);
+                Some(Projection {
+                    position,
+                    end: position + enemy.velocity() * ticks as f32,
+                    intercept: quickest
+                        .filter(|quickest| *quickest <= ticks)
+                        .map(|quickest| position + enemy.velocity() * quickest as f32),
+                })
+            })
+            .collect();
+
+        let threats = world
+            .values()
+            .filter(|entity| entity.city.is_some() && entity.is_alive())
+            .filter_map(|city| {
+                let ticks = enemies
+                    .iter()
+                    .filter_map(|enemy| ticks_to_hit(enemy, city, ticks_left(enemy, level)))
+                    .min()?;
+                let position = match entity_shape(city)? {
+                    Shape::Rect(rect) => vec2(rect.center().x, rect.y),
+                    _ => city.position?.0,
+                };
+                Some(Threat { position, ticks })
+            })
+            .collect();
+
+        AimAssist {
+            projections,
+            threats,
+            shots,
+        }
+    }
+}
+
+/// The shots firing at `aim` can make with `controls`: a click fires from
+/// the closest launcher, keys from any launcher, if it has ammo left.
+pub fn shots(world: &World, aim: Vec2, controls: ControlScheme) -> Vec<Shot> {
+    let launchers = match controls {
+        ControlScheme::Closest => get_closest_launcher(world, aim).into_iter().collect(),
+        ControlScheme::Keys => launcher_ids(world),
+    };
+    launchers
+        .into_iter()
+        .filter_map(|id| world.get(id))
+        .filter(|entity| entity.launcher.is_some_and(|launcher| launcher.ammo > 0))
+        .filter_map(|entity| {
+            // the same place fire_missile launches from
+            let origin = entity.position?.0 + vec2(CANNON_BASE_WIDTH / 2.0, 0.0);
+            Some(Shot {
+                origin,
+                aim,
+                radius: MISSILE_RADIUS,
+                ticks: flight_ticks(origin.distance(aim), PLAYER_MISSILE_SPEED),
+            })
+        })
+        .collect()
+}
+
+/// Ticks until a missile detonates at its target, or leaves `level` when it
+/// has none.
+pub fn ticks_left(missile: &Components, level: Rect) -> u32 {
+    if let Some(lifetime) = missile.lifetime {
+        return lifetime.0;
+    }
+    let (Some(position), velocity) = (missile.position, missile.velocity()) else {
+        return 0;
+    };
+    let position = position.0;
+    let axis_ticks = |position: f32, speed: f32, low: f32, high: f32| {
+        if speed > 0.0 {
+            (high - position) / speed
+        } else if speed < 0.0 {
+            (low - position) / speed
+        } else {
+            f32::INFINITY
+        }
+    };
+    let ticks = axis_ticks(position.x, velocity.x, level.x, level.right()).min(axis_ticks(
+        position.y,
+        velocity.y,
+        level.y,
+        level.bottom(),
+    ));
+    if ticks.is_finite() {
+        ticks.max(0.0).ceil() as u32
+    } else {
+        0
+    }
+}
+
+/// First tick, within `ticks`, at which `missile` flying straight on hits
+/// `target`. Hit tests the whole path at once then halves it, so it takes a
+/// handful of sweeps whatever the distance.
+pub fn ticks_to_hit(missile: &Components, target: &Components, ticks: u32) -> Option<u32> {
+    let position = missile.position?.0;
+    let velocity = missile.velocity();
+    let collider = missile.collider.as_ref()?;
+    let target = entity_shape(target)?;
+    // the sweep covers the path from the start to `ticks` on
+    let reaches = |ticks: u32| {
+        let motion = velocity * ticks as f32;
+        let shape = collider_shape(position + motion, velocity, collider);
+        sweep_intersects(&shape, motion, &target)
+    };
+
+    if !reaches(ticks) {
+        return None;
+    }
+    let (mut low, mut high) = (0, ticks);
+    while low < high {
+        let middle = (low + high) / 2;
+        if reaches(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Some(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{AnimationState, LAUNCHER_FIRE};
+    use crate::data::{Warhead, MISSILE_COLLIDER, MISSILE_SIZE};
+    use crate::ecs::{City, Collider, Health, Launcher, Lifetime, Missile, Position, Velocity};
+
+    fn enemy(position: Vec2, velocity: Vec2, lifetime: Option<u32>) -> Components {
+        Components {
+            position: Some(Position(position)),
+            velocity: Some(Velocity(velocity)),
+            collider: Some(MISSILE_COLLIDER),
+            team: Some(Team::Enemy),
+            health: Some(Health(1)),
+            lifetime: lifetime.map(Lifetime),
+            missile: Some(Missile {
+                trail_length: 0.0,
+                target: None,
+                warhead: Warhead::Standard,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn city(x: f32, y: f32) -> Components {
+        Components {
+            position: Some(Position(vec2(x, y))),
+            collider: Some(Collider::Rect {
+                size: vec2(64.0, 64.0),
+            }),
+            city: Some(City),
+            health: Some(Health(1)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn missiles_are_timed_to_the_cities_in_their_way() {
+        let level = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let falling = enemy(vec2(132.0, 0.0), vec2(0.0, 2.0), None);
+        assert_eq!(ticks_left(&falling, level), 300);
+
+        // the missile's tip gets past the roof at y 501 before its centre does
+        let ticks = ticks_to_hit(&falling, &city(100.0, 501.0), 300).unwrap();
+        assert_eq!(ticks, ((501.0 - MISSILE_SIZE / 2.0) / 2.0).ceil() as u32);
+        assert_eq!(ticks_to_hit(&falling, &city(400.0, 500.0), 300), None);
+
+        // aimed missiles stop at their target
+        let aimed = enemy(vec2(132.0, 0.0), vec2(0.0, 2.0), Some(100));
+        assert_eq!(ticks_left(&aimed, level), 100);
+        assert_eq!(ticks_to_hit(&aimed, &city(100.0, 500.0), 100), None);
+    }
+
+    #[test]
+    fn shots_come_from_the_launchers_the_controls_pick() {
+        let mut world = World::new();
+        for (x, ammo) in [(0.0, 10), (400.0, 10), (800.0, 0)] {
+            world.spawn(Components {
+                position: Some(Position(vec2(x, 550.0))),
+                launcher: Some(Launcher {
+                    target: Vec2::ZERO,
+                    ammo,
+                    max_ammo: 10,
+                    barrel: AnimationState::done(LAUNCHER_FIRE),
+                }),
+                ..Default::default()
+            });
+        }
+
+        let aim = vec2(416.0, 300.0);
+        let closest = shots(&world, aim, ControlScheme::Closest);
+        assert_eq!(closest.len(), 1);
+        assert_eq!(
+            closest[0].origin,
+            vec2(400.0 + CANNON_BASE_WIDTH / 2.0, 550.0)
+        );
+        assert_eq!(
+            closest[0].ticks,
+            (250.0 / PLAYER_MISSILE_SPEED).ceil() as u32
+        );
+        assert_eq!(closest[0].radius, MISSILE_RADIUS);
+
+        // the empty launcher on the right cannot fire, even where it is closest
+        let origins =
+            |shots: Vec<Shot>| -> Vec<f32> { shots.iter().map(|shot| shot.origin.x).collect() };
+        let right = vec2(800.0, 300.0);
+        let keys = shots(&world, right, ControlScheme::Keys);
+        assert_eq!(
+            origins(keys),
+            vec![CANNON_BASE_WIDTH / 2.0, 400.0 + CANNON_BASE_WIDTH / 2.0]
+        );
+        assert!(shots(&world, right, ControlScheme::Closest).is_empty());
+        assert!(shots(&World::new(), aim, ControlScheme::Closest).is_empty());
+    }
+}
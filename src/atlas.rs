@@ -0,0 +1,162 @@
+use crate::data::Assets;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+/// Every PNG in this assets folder ends up in the atlas, named after its file
+/// without the extension.
+const SPRITES_FOLDER: &str = "sprites/";
+/// Atlases are at least this wide, wider only for a wider sprite.
+const ATLAS_WIDTH: u16 = 512;
+/// Empty texels around every sprite so that scaled sprites do not pick up
+/// their neighbours' edges.
+const PADDING: u16 = 2;
+
+/// All sprites packed into a single texture, so drawing them does not switch
+/// textures and macroquad can batch the draw calls.
+pub struct Atlas {
+    pub texture: Texture2D,
+    regions: HashMap<String, Rect>,
+}
+
+impl Atlas {
+    pub fn load() -> Atlas {
+        let (image, regions) = pack_sprites();
+        let texture = Texture2D::from_image(&image);
+        texture.set_filter(FilterMode::Nearest);
+        Atlas { texture, regions }
+    }
+
+    /// Where the sprite `name` is in the atlas. Panics for a sprite that is
+    /// not in the assets, like loading the file would.
+    pub fn region(&self, name: &str) -> Rect {
+        match self.regions.get(name) {
+            Some(region) => *region,
+            None => panic!("no sprite named {} in the atlas", name),
+        }
+    }
+
+    /// `source` relative to the sprite `name`, the whole sprite when `None`.
+    pub fn source(&self, name: &str, source: Option<Rect>) -> Rect {
+        let region = self.region(name);
+        match source {
+            Some(source) => source.offset(region.point()),
+            None => region,
+        }
+    }
+}
+
+/// Places rects of `sizes` without overlap, tallest first, row by row.
+/// Returns the atlas size and where each one went, in the order given.
+pub fn pack(sizes: &[(u16, u16)]) -> ((u16, u16), Vec<(u16, u16)>) {
+    let width = sizes
+        .iter()
+        .map(|&(w, _)| w + PADDING * 2)
+        .fold(ATLAS_WIDTH, u16::max);
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(sizes[index].1));
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for index in order {
+        let (w, h) = (sizes[index].0 + PADDING * 2, sizes[index].1 + PADDING * 2);
+        if x + w > width {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+        positions[index] = (x + PADDING, y + PADDING);
+        x += w;
+        row_height = row_height.max(h);
+    }
+
+    ((width, y + row_height), positions)
+}
+
+pub fn pack_sprites() -> (Image, HashMap<String, Rect>) {
+    // sorted, so the atlas comes out the same whatever order files are listed in
+    let mut files: Vec<_> = Assets::iter()
+        .filter(|file| file.starts_with(SPRITES_FOLDER) && file.ends_with(".png"))
+        .collect();
+    files.sort();
+
+    let sprites: Vec<(String, Image)> = files
+        .iter()
+        .map(|file| {
+            let name = &file[SPRITES_FOLDER.len()..file.len() - ".png".len()];
+            let data = Assets::get(file).unwrap().data;
+            let image = Image::from_file_with_format(&data, Some(ImageFormat::Png))
+                .unwrap_or_else(|error| panic!("{}: {}", file, error));
+            (name.to_string(), image)
+        })
+        .collect();
+
+    let sizes: Vec<_> = sprites
+        .iter()
+        .map(|(_, image)| (image.width, image.height))
+        .collect();
+    let ((width, height), positions) = pack(&sizes);
+    let mut atlas = Image::gen_image_color(width, height, BLANK);
+    let mut regions = HashMap::new();
+    for ((name, image), (x, y)) in sprites.into_iter().zip(positions) {
+        let row = image.width as usize * 4;
+        for line in 0..image.height as usize {
+            let start = ((y as usize + line) * width as usize + x as usize) * 4;
+            atlas.bytes[start..start + row].copy_from_slice(&image.bytes[line * row..][..row]);
+        }
+        let region = Rect::new(x as f32, y as f32, image.width as f32, image.height as f32);
+        regions.insert(name, region);
+    }
+
+    (atlas, regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_rects_keep_apart_and_inside_the_atlas() {
+        let sizes = [(100, 20), (300, 40), (500, 10), (8, 8), (700, 30), (64, 64)];
+        let ((width, height), positions) = pack(&sizes);
+        assert!(width >= 700 + PADDING * 2);
+
+        let rects: Vec<Rect> = sizes
+            .iter()
+            .zip(&positions)
+            .map(|(&(w, h), &(x, y))| Rect::new(x as f32, y as f32, w as f32, h as f32))
+            .collect();
+        let atlas = Rect::new(0.0, 0.0, width as f32, height as f32);
+        for (index, rect) in rects.iter().enumerate() {
+            let padded = Rect::new(
+                rect.x - PADDING as f32,
+                rect.y - PADDING as f32,
+                rect.w + PADDING as f32 * 2.0,
+                rect.h + PADDING as f32 * 2.0,
+            );
+            assert!(atlas.contains(padded.point()));
+            assert!(padded.right() <= atlas.right() && padded.bottom() <= atlas.bottom());
+            for other in &rects[index + 1..] {
+                assert!(padded.intersect(*other).is_none(), "{:?} {:?}", rect, other);
+            }
+        }
+    }
+
+    #[test]
+    fn every_sprite_is_copied_into_its_region() {
+        let (atlas, regions) = pack_sprites();
+        let plane = regions["plane"];
+        let data = Assets::get("sprites/plane.png").unwrap().data;
+        let image = Image::from_file_with_format(&data, Some(ImageFormat::Png)).unwrap();
+        let (width, height) = (image.width as u32, image.height as u32);
+        assert_eq!((plane.w, plane.h), (width as f32, height as f32));
+
+        for (x, y) in [(0, 0), (width / 2, height / 2), (width - 1, height - 1)] {
+            assert_eq!(
+                atlas.get_pixel(plane.x as u32 + x, plane.y as u32 + y),
+                image.get_pixel(x, y)
+            );
+        }
+        assert!(regions.contains_key("missile_launcher_part_2"));
+        assert!(!regions.contains_key("background"));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::data::{GameEvent, HitTarget};
+use macroquad::prelude::*;
+
+/// Largest shake at full trauma, in level units and degrees.
+const MAX_SHAKE_OFFSET: f32 = 8.0;
+const MAX_SHAKE_ANGLE: f32 = 1.5;
+/// Trauma lost every tick, a full shake settles in a bit under a second.
+const TRAUMA_DECAY: f32 = 0.02;
+const FLASH_TICKS: u32 = 12;
+/// Ticks run in slow motion after the last city is hit, one every
+/// `SLOW_MOTION_FRAMES` frames.
+const SLOW_MOTION_TICKS: u32 = 90;
+const SLOW_MOTION_FRAMES: u32 = 4;
+
+/// Everything can be turned off for players who get motion sick or are
+/// sensitive to flashes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraFxSettings {
+    /// Scales the shake, 0 turns it off.
+    pub shake: f32,
+    pub flash: bool,
+    pub slow_motion: bool,
+}
+
+impl Default for CameraFxSettings {
+    fn default() -> Self {
+        CameraFxSettings {
+            shake: 1.0,
+            flash: true,
+            slow_motion: true,
+        }
+    }
+}
+
+impl CameraFxSettings {
+    pub fn off() -> CameraFxSettings {
+        CameraFxSettings {
+            shake: 0.0,
+            flash: false,
+            slow_motion: false,
+        }
+    }
+}
+
+/// Screen shake, flashes and slow motion. They only change how a frame is
+/// drawn: `Game::camera` stays put, so aiming through `screen_to_world` is
+/// not thrown off. Slow motion runs after the game is over and only paces
+/// the effects.
+#[derive(Debug, Default)]
+pub struct CameraFx {
+    pub settings: CameraFxSettings,
+    trauma: f32,
+    flash_ticks: u32,
+    slow_motion_ticks: u32,
+    time: u32,
+    frame: u32,
+}
+
+impl CameraFx {
+    pub fn new(settings: CameraFxSettings) -> CameraFx {
+        CameraFx {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// The hit that takes the last city out plays in slow motion.
+    pub fn react(&mut self, events: &[GameEvent], cities_left: usize) {
+        for event in events {
+            match event {
+                GameEvent::Hit {
+                    target: HitTarget::City,
+                    ..
+                } => {
+                    self.add_trauma(0.6);
+                    if self.settings.flash {
+                        self.flash_ticks = FLASH_TICKS;
+                    }
+                    if cities_left == 0 && self.settings.slow_motion {
+                        self.slow_motion_ticks = SLOW_MOTION_TICKS;
+                    }
+                }
+                GameEvent::Hit {
+                    target: HitTarget::Missile,
+                    ..
+                } => self.add_trauma(0.05),
+                GameEvent::Hit { .. } => self.add_trauma(0.2),
+                GameEvent::Detonation { .. } => self.add_trauma(0.25),
+            }
+        }
+    }
+
+    /// Advances the effects by a tick, so they freeze while the game is paused.
+    pub fn tick(&mut self) {
+        self.time += 1;
+        self.trauma = (self.trauma - TRAUMA_DECAY).max(0.0);
+        self.flash_ticks = self.flash_ticks.saturating_sub(1);
+        self.slow_motion_ticks = self.slow_motion_ticks.saturating_sub(1);
+    }
+
+    pub fn slow_motion(&self) -> bool {
+        self.slow_motion_ticks > 0
+    }
+
+    /// Whether a game tick runs this frame. Called once per frame.
+    pub fn tick_this_frame(&mut self) -> bool {
+        self.frame = self.frame.wrapping_add(1);
+        !self.slow_motion() || self.frame.is_multiple_of(SLOW_MOTION_FRAMES)
+    }
+
+    pub fn shaken(&self, camera: &Camera2D) -> Camera2D {
+        // squared, so small knocks barely move while big ones rattle
+        let shake = self.trauma * self.trauma * self.settings.shake;
+        let time = self.time as f32;
+        let offset = vec2((time * 1.7).sin(), (time * 2.3 + 1.0).sin()) * MAX_SHAKE_OFFSET;
+        Camera2D {
+            target: camera.target + offset * shake,
+            rotation: camera.rotation + (time * 1.3 + 2.0).sin() * MAX_SHAKE_ANGLE * shake,
+            zoom: camera.zoom,
+            offset: camera.offset,
+            render_target: camera.render_target.clone(),
+            viewport: camera.viewport,
+        }
+    }
+
+    pub fn flash(&self) -> f32 {
+        self.flash_ticks as f32 / FLASH_TICKS as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn city_hit() -> GameEvent {
+        GameEvent::Hit {
+            position: Vec2::ZERO,
+            target: HitTarget::City,
+            target_bounds: Rect::new(0.0, 0.0, 64.0, 64.0),
+        }
+    }
+
+    #[test]
+    fn city_hits_shake_and_flash_until_they_settle() {
+        let camera = Camera2D::default();
+        let mut fx = CameraFx::new(CameraFxSettings::default());
+        fx.react(&[city_hit()], 2);
+        fx.tick();
+        assert_ne!(fx.shaken(&camera).target, camera.target);
+        assert!(fx.flash() > 0.0);
+        assert!(!fx.slow_motion());
+
+        for _ in 0..100 {
+            fx.tick();
+        }
+        assert_eq!(fx.shaken(&camera).target, camera.target);
+        assert_eq!(fx.flash(), 0.0);
+    }
+
+    #[test]
+    fn detonations_shake_without_flashing() {
+        let camera = Camera2D::default();
+        let mut fx = CameraFx::new(CameraFxSettings::default());
+        let detonations = vec![
+            GameEvent::Detonation {
+                position: Vec2::ZERO
+            };
+            12
+        ];
+        fx.react(&detonations, 2);
+        fx.tick();
+        assert_ne!(fx.shaken(&camera).target, camera.target);
+        assert_eq!(fx.flash(), 0.0);
+    }
+
+    #[test]
+    fn last_city_plays_in_slow_motion() {
+        let mut fx = CameraFx::new(CameraFxSettings::default());
+        fx.react(&[city_hit()], 0);
+        assert!(fx.slow_motion());
+        let ticked = (0..SLOW_MOTION_FRAMES * 2)
+            .filter(|_| fx.tick_this_frame())
+            .count();
+        assert_eq!(ticked, 2);
+    }
+
+    #[test]
+    fn turned_off_effects_stay_off() {
+        let camera = Camera2D::default();
+        let mut fx = CameraFx::new(CameraFxSettings::off());
+        fx.react(&[city_hit()], 0);
+        fx.tick();
+        assert_eq!(fx.shaken(&camera).target, camera.target);
+        assert_eq!(fx.flash(), 0.0);
+        assert!(!fx.slow_motion());
+        assert!(fx.tick_this_frame());
+    }
+}
@@ -0,0 +1,45 @@
+use macroquad::prelude::*;
+
+/// An offscreen texture the size of the level. The game is drawn on it in
+/// level units whatever the window size, then the texture is scaled into the
+/// window with black bars around it.
+pub struct Canvas {
+    target: RenderTarget,
+    size: Vec2,
+}
+
+impl Canvas {
+    pub fn new(level_size: Vec2) -> Canvas {
+        let target = render_target(level_size.x as u32, level_size.y as u32);
+        target.texture.set_filter(FilterMode::Nearest);
+        Canvas {
+            target,
+            size: level_size,
+        }
+    }
+
+    /// Camera that draws onto the canvas, one level unit to a texel.
+    pub fn camera(&self) -> Camera2D {
+        let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, self.size.x, self.size.y));
+        camera.render_target = Some(self.target.clone());
+        camera
+    }
+
+    /// `window_camera` fits the level into the window, see
+    /// `fit_camera_to_level`.
+    pub fn present(&self, window_camera: &Camera2D) {
+        set_camera(window_camera);
+        clear_background(BLACK);
+        draw_texture_ex(
+            &self.target.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(self.size),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+}
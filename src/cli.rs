@@ -0,0 +1,178 @@
+use crate::camera_fx::CameraFxSettings;
+use crate::input::ControlScheme;
+use crate::particles::DEFAULT_MAX_PARTICLES;
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    /// Seeds the run instead of the level's seed or the clock.
+    pub seed: Option<u64>,
+    /// Plays a recorded run back instead of taking the player's input.
+    pub replay: Option<String>,
+    /// Where the run is recorded to, `DEFAULT_RECORD_PATH` without it.
+    pub record: Option<String>,
+    pub controls: ControlScheme,
+    /// Particles alive at once, lower it on slow machines. 0 turns them off.
+    pub max_particles: usize,
+    pub camera_fx: CameraFxSettings,
+    /// Code of the UI language, like `pl`. English without it.
+    pub language: Option<String>,
+    /// Palette preset, like `colour_blind`. `arcade` without it.
+    pub palette: Option<String>,
+    /// Starts with the aim assist overlay on.
+    pub aim_assist: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            seed: None,
+            replay: None,
+            record: None,
+            controls: ControlScheme::default(),
+            max_particles: DEFAULT_MAX_PARTICLES,
+            camera_fx: CameraFxSettings::default(),
+            language: None,
+            palette: None,
+            aim_assist: false,
+        }
+    }
+}
+
+pub const USAGE: &str =
+    "usage: missile_commander [--seed <number>] [--replay <file>] [--record <file>] \
+    [--controls closest|keys] [--max-particles <number>] \
+    [--shake <scale>] [--no-flash] [--no-slow-motion] [--reduced-motion] \
+    [--language <code>] [--palette <name>] \
+    [--aim-assist]";
+pub const DEFAULT_RECORD_PATH: &str = "replay.json";
+
+impl Options {
+    /// Parses the arguments that follow the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("invalid seed: {}", value))?;
+                    options.seed = Some(seed);
+                }
+                "--replay" => {
+                    options.replay = Some(args.next().ok_or("--replay needs a file")?);
+                }
+                "--record" => {
+                    options.record = Some(args.next().ok_or("--record needs a file")?);
+                }
+                "--controls" => {
+                    let name = args.next().ok_or("--controls needs a scheme")?;
+                    options.controls = ControlScheme::from_name(&name)
+                        .ok_or_else(|| format!("unknown control scheme: {}", name))?;
+                }
+                "--max-particles" => {
+                    let value = args.next().ok_or("--max-particles needs a value")?;
+                    options.max_particles = value
+                        .parse()
+                        .map_err(|_| format!("invalid particle count: {}", value))?;
+                }
+                "--shake" => {
+                    let value = args.next().ok_or("--shake needs a scale")?;
+                    options.camera_fx.shake = value
+                        .parse()
+                        .ok()
+                        .filter(|scale: &f32| *scale >= 0.0)
+                        .ok_or_else(|| format!("invalid shake scale: {}", value))?;
+                }
+                "--no-flash" => options.camera_fx.flash = false,
+                "--no-slow-motion" => options.camera_fx.slow_motion = false,
+                "--reduced-motion" => options.camera_fx = CameraFxSettings::off(),
+                "--language" => {
+                    options.language = Some(args.next().ok_or("--language needs a code")?);
+                }
+                "--palette" => {
+                    options.palette = Some(args.next().ok_or("--palette needs a name")?);
+                }
+                "--aim-assist" => options.aim_assist = true,
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_seed() {
+        assert_eq!(parse(&[]), Ok(Options::default()));
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--speed", "1"]).is_err());
+    }
+
+    #[test]
+    fn parses_replay_files() {
+        let options = parse(&["--replay", "bug.json", "--record", "run.json"]).unwrap();
+        assert_eq!(options.replay.as_deref(), Some("bug.json"));
+        assert_eq!(options.record.as_deref(), Some("run.json"));
+        assert!(parse(&["--replay"]).is_err());
+    }
+
+    #[test]
+    fn parses_control_scheme() {
+        assert_eq!(parse(&[]).unwrap().controls, ControlScheme::Closest);
+        let options = parse(&["--controls", "keys"]).unwrap();
+        assert_eq!(options.controls, ControlScheme::Keys);
+        assert!(parse(&["--controls", "joystick"]).is_err());
+    }
+
+    #[test]
+    fn parses_particle_cap() {
+        assert_eq!(parse(&[]).unwrap().max_particles, DEFAULT_MAX_PARTICLES);
+        assert_eq!(parse(&["--max-particles", "0"]).unwrap().max_particles, 0);
+        assert!(parse(&["--max-particles", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_camera_effect_settings() {
+        let options = parse(&["--shake", "0.5", "--no-flash"]).unwrap();
+        assert_eq!(options.camera_fx.shake, 0.5);
+        assert!(!options.camera_fx.flash);
+        assert!(options.camera_fx.slow_motion);
+        let options = parse(&["--reduced-motion"]).unwrap();
+        assert_eq!(options.camera_fx, CameraFxSettings::off());
+        assert!(parse(&["--shake", "-2"]).is_err());
+    }
+
+    #[test]
+    fn parses_language() {
+        assert_eq!(parse(&[]).unwrap().language, None);
+        let options = parse(&["--language", "pl"]).unwrap();
+        assert_eq!(options.language.as_deref(), Some("pl"));
+        assert!(parse(&["--language"]).is_err());
+    }
+
+    #[test]
+    fn parses_aim_assist() {
+        assert!(!parse(&[]).unwrap().aim_assist);
+        assert!(parse(&["--aim-assist"]).unwrap().aim_assist);
+    }
+
+    #[test]
+    fn parses_palette() {
+        assert_eq!(parse(&[]).unwrap().palette, None);
+        let options = parse(&["--palette", "colour_blind"]).unwrap();
+        assert_eq!(options.palette.as_deref(), Some("colour_blind"));
+        assert!(parse(&["--palette"]).is_err());
+    }
+}
@@ -0,0 +1,412 @@
+use crate::ecs::Collider;
+use macroquad::prelude::*;
+
+/// Rectangles are anchored at their top-left corner, capsules at their centre
+/// line, the same way the entities are stored and drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Rect(Rect),
+    Capsule { start: Vec2, end: Vec2, radius: f32 },
+}
+
+/// The world-space shape of a collider at `position`. Capsules point along
+/// `velocity`.
+pub fn collider_shape(position: Vec2, velocity: Vec2, collider: &Collider) -> Shape {
+    match *collider {
+        Collider::Rect { size } => Shape::Rect(Rect::new(position.x, position.y, size.x, size.y)),
+        Collider::Capsule { length, radius } => {
+            let half_length = velocity.normalize_or_zero() * (length / 2.0 - radius);
+            Shape::Capsule {
+                start: position - half_length,
+                end: position + half_length,
+                radius,
+            }
+        }
+    }
+}
+
+pub fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    start + segment * t
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn segments_intersect(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
+    let d1 = cross(b2 - b1, a1 - b1);
+    let d2 = cross(b2 - b1, a2 - b1);
+    let d3 = cross(a2 - a1, b1 - a1);
+    let d4 = cross(a2 - a1, b2 - a1);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+
+    // collinear and touching cases fall back to the endpoint distances
+    false
+}
+
+pub fn segment_segment_distance(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> f32 {
+    if segments_intersect(a1, a2, b1, b2) {
+        return 0.0;
+    }
+
+    [
+        a1.distance(closest_point_on_segment(a1, b1, b2)),
+        a2.distance(closest_point_on_segment(a2, b1, b2)),
+        b1.distance(closest_point_on_segment(b1, a1, a2)),
+        b2.distance(closest_point_on_segment(b2, a1, a2)),
+    ]
+    .into_iter()
+    .fold(f32::INFINITY, f32::min)
+}
+
+pub fn segment_rect_distance(start: Vec2, end: Vec2, rect: &Rect) -> f32 {
+    if rect.contains(start) || rect.contains(end) {
+        return 0.0;
+    }
+
+    let top_left = vec2(rect.x, rect.y);
+    let top_right = vec2(rect.x + rect.w, rect.y);
+    let bottom_left = vec2(rect.x, rect.y + rect.h);
+    let bottom_right = vec2(rect.x + rect.w, rect.y + rect.h);
+    [
+        (top_left, top_right),
+        (top_right, bottom_right),
+        (bottom_right, bottom_left),
+        (bottom_left, top_left),
+    ]
+    .into_iter()
+    .map(|(edge_start, edge_end)| segment_segment_distance(start, end, edge_start, edge_end))
+    .fold(f32::INFINITY, f32::min)
+}
+
+/// Rectangles only overlap when they share some area, so touching edges do not count.
+pub fn rects_overlap(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+}
+
+pub fn intersects(a: &Shape, b: &Shape) -> bool {
+    match (*a, *b) {
+        (Shape::Rect(a), Shape::Rect(b)) => rects_overlap(&a, &b),
+        (Shape::Capsule { start, end, radius }, Shape::Rect(rect))
+        | (Shape::Rect(rect), Shape::Capsule { start, end, radius }) => {
+            segment_rect_distance(start, end, &rect) < radius
+        }
+        (
+            Shape::Capsule {
+                start: a1,
+                end: a2,
+                radius: radius_a,
+            },
+            Shape::Capsule {
+                start: b1,
+                end: b2,
+                radius: radius_b,
+            },
+        ) => segment_segment_distance(a1, a2, b1, b2) < radius_a + radius_b,
+    }
+}
+
+/// The shape as a convex hull of points plus the radius around it.
+fn hull(shape: &Shape) -> (Vec<Vec2>, f32) {
+    match *shape {
+        Shape::Rect(rect) => (
+            vec![
+                vec2(rect.x, rect.y),
+                vec2(rect.x + rect.w, rect.y),
+                vec2(rect.x + rect.w, rect.y + rect.h),
+                vec2(rect.x, rect.y + rect.h),
+            ],
+            0.0,
+        ),
+        Shape::Capsule { start, end, radius } => (vec![start, end], radius),
+    }
+}
+
+fn half_hull<'a>(points: impl Iterator<Item = &'a Vec2>) -> Vec<Vec2> {
+    let mut hull: Vec<Vec2> = vec![];
+    for point in points {
+        while hull.len() >= 2
+            && cross(
+                hull[hull.len() - 1] - hull[hull.len() - 2],
+                *point - hull[hull.len() - 2],
+            ) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    hull.pop();
+
+    hull
+}
+
+/// Convex hull in counter-clockwise order (monotone chain).
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull = half_hull(points.iter());
+    hull.extend(half_hull(points.iter().rev()));
+
+    hull
+}
+
+fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    (0..points.len()).map(move |i| (points[i], points[(i + 1) % points.len()]))
+}
+
+fn strictly_inside(point: Vec2, polygon: &[Vec2]) -> bool {
+    polygon.len() >= 3 && edges(polygon).all(|(start, end)| cross(end - start, point - start) > 0.0)
+}
+
+fn centroid(points: &[Vec2]) -> Vec2 {
+    points.iter().sum::<Vec2>() / points.len() as f32
+}
+
+/// Whether two convex hulls overlap or come closer than `reach` to each other.
+fn hulls_within(a: &[Vec2], b: &[Vec2], reach: f32) -> bool {
+    let crossing =
+        edges(a).any(|(a1, a2)| edges(b).any(|(b1, b2)| segments_intersect(a1, a2, b1, b2)));
+    let inside = a
+        .iter()
+        .chain([centroid(a)].iter())
+        .any(|point| strictly_inside(*point, b))
+        || b.iter()
+            .chain([centroid(b)].iter())
+            .any(|point| strictly_inside(*point, a));
+    if crossing || inside {
+        return true;
+    }
+
+    edges(a)
+        .flat_map(|(a1, a2)| edges(b).map(move |(b1, b2)| segment_segment_distance(a1, a2, b1, b2)))
+        .any(|distance| distance < reach)
+}
+
+/// Axis-aligned box around everything the shape covers while travelling
+/// `motion`, ending where it is now.
+pub fn swept_bounds(shape: &Shape, motion: Vec2) -> Rect {
+    let (points, radius) = hull(shape);
+    let mut min = vec2(f32::INFINITY, f32::INFINITY);
+    let mut max = vec2(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for point in points.iter().flat_map(|point| [*point - motion, *point]) {
+        min = min.min(point);
+        max = max.max(point);
+    }
+
+    Rect::new(
+        min.x - radius,
+        min.y - radius,
+        max.x - min.x + radius * 2.0,
+        max.y - min.y + radius * 2.0,
+    )
+}
+
+/// Continuous version of [`intersects`]. `moving` is where the shape ended up
+/// after travelling `motion` in the last step, relative to `other`. Every
+/// position in between is tested, so fast shapes cannot skip past thin ones.
+pub fn sweep_intersects(moving: &Shape, motion: Vec2, other: &Shape) -> bool {
+    if motion == Vec2::ZERO {
+        return intersects(moving, other);
+    }
+
+    let (points, radius) = hull(moving);
+    let swept = convex_hull(
+        points
+            .iter()
+            .flat_map(|point| [*point - motion, *point])
+            .collect(),
+    );
+    let (other_points, other_radius) = hull(other);
+    hulls_within(&swept, &other_points, radius + other_radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::MISSILE_COLLIDER;
+
+    /// A missile at `(x, y)` flying at `velocity`, shaped the way the game
+    /// shapes them.
+    fn missile(x: f32, y: f32, velocity: Vec2) -> Shape {
+        collider_shape(vec2(x, y), velocity, &MISSILE_COLLIDER)
+    }
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> Shape {
+        Shape::Rect(Rect::new(x, y, w, h))
+    }
+
+    fn capsule(start: (f32, f32), end: (f32, f32), radius: f32) -> Shape {
+        Shape::Capsule {
+            start: vec2(start.0, start.1),
+            end: vec2(end.0, end.1),
+            radius,
+        }
+    }
+
+    #[test]
+    fn rects_use_their_own_width_and_height() {
+        // a wide, flat plane and a box below it: using the width for both
+        // axes would make the plane 64 px tall and report a hit
+        let plane = rect(0.0, 0.0, 64.0, 8.0);
+        let below = rect(0.0, 20.0, 8.0, 8.0);
+        assert!(!intersects(&plane, &below));
+        assert!(!intersects(&below, &plane));
+
+        // a tall, thin rect next to a wide one whose height is smaller than its width
+        let tower = rect(0.0, 0.0, 8.0, 64.0);
+        let beside = rect(20.0, 40.0, 32.0, 8.0);
+        assert!(!intersects(&tower, &beside));
+        assert!(intersects(&tower, &rect(4.0, 40.0, 32.0, 8.0)));
+    }
+
+    #[test]
+    fn rects_are_anchored_at_top_left() {
+        let building = rect(100.0, 100.0, 64.0, 64.0);
+        assert!(intersects(&building, &rect(160.0, 160.0, 10.0, 10.0)));
+        assert!(!intersects(&building, &rect(70.0, 70.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn touching_edges_do_not_collide() {
+        let a = rect(0.0, 0.0, 32.0, 32.0);
+        assert!(!intersects(&a, &rect(32.0, 0.0, 32.0, 32.0)));
+        assert!(!intersects(&a, &rect(0.0, 32.0, 32.0, 32.0)));
+    }
+
+    #[test]
+    fn diagonal_capsule_misses_rect_inside_its_bounding_box() {
+        let plane = rect(0.0, 0.0, 16.0, 16.0);
+        let missile = capsule((0.0, 40.0), (40.0, 0.0), 2.0);
+        assert!(!intersects(&missile, &plane));
+    }
+
+    #[test]
+    fn capsule_crossing_rect_hits_without_endpoints_inside() {
+        let plane = rect(0.0, 0.0, 32.0, 8.0);
+        let missile = capsule((16.0, -20.0), (16.0, 28.0), 1.0);
+        assert!(intersects(&missile, &plane));
+    }
+
+    #[test]
+    fn capsule_radius_extends_reach() {
+        let plane = rect(0.0, 0.0, 32.0, 8.0);
+        let missile = capsule((16.0, -20.0), (16.0, -3.0), 4.0);
+        assert!(intersects(&missile, &plane));
+        assert!(!intersects(
+            &capsule((16.0, -20.0), (16.0, -5.0), 4.0),
+            &plane
+        ));
+    }
+
+    #[test]
+    fn capsules_against_capsules() {
+        let a = capsule((0.0, 0.0), (20.0, 0.0), 2.0);
+        assert!(intersects(&a, &capsule((10.0, -10.0), (10.0, 10.0), 2.0)));
+        assert!(intersects(&a, &capsule((0.0, 3.0), (20.0, 3.0), 2.0)));
+        assert!(!intersects(&a, &capsule((0.0, 5.0), (20.0, 5.0), 2.0)));
+    }
+
+    #[test]
+    fn missile_shape_follows_heading_from_its_center() {
+        let building = rect(90.0, 110.0, 64.0, 64.0);
+        assert!(intersects(
+            &missile(100.0, 100.0, vec2(0.0, 1.0)),
+            &building
+        ));
+
+        // rotated sideways the same missile no longer reaches down into the building
+        assert!(!intersects(
+            &missile(100.0, 100.0, vec2(1.0, 0.0)),
+            &building
+        ));
+    }
+
+    #[test]
+    fn sweep_catches_fast_missile_passing_through_thin_plane() {
+        let plane = rect(0.0, 100.0, 64.0, 8.0);
+        // one step took the missile from above the plane to well below it
+        let velocity = vec2(0.0, 150.0);
+        let through = missile(32.0, 200.0, velocity);
+        assert!(!intersects(&through, &plane));
+        assert!(sweep_intersects(&through, velocity, &plane));
+
+        // the same step a little to the side misses
+        let beside = missile(90.0, 200.0, velocity);
+        assert!(!sweep_intersects(&beside, velocity, &plane));
+    }
+
+    #[test]
+    fn sweep_catches_head_on_missiles_swapping_places() {
+        let (player_velocity, enemy_velocity) = (vec2(0.0, -80.0), vec2(0.0, 40.0));
+        let player = missile(100.0, 60.0, player_velocity);
+        let enemy = missile(100.0, 140.0, enemy_velocity);
+        assert!(!intersects(&player, &enemy));
+        assert!(sweep_intersects(
+            &player,
+            player_velocity - enemy_velocity,
+            &enemy
+        ));
+    }
+
+    #[test]
+    fn sweep_uses_relative_motion_for_crossing_paths() {
+        // both paths go through (100, 100), but the enemy gets there after the player left
+        let (player_velocity, enemy_velocity) = (vec2(0.0, -200.0), vec2(-200.0, 0.0));
+        let player = missile(100.0, 0.0, player_velocity);
+        let late = missile(50.0, 100.0, enemy_velocity);
+        assert!(!sweep_intersects(
+            &player,
+            player_velocity - enemy_velocity,
+            &late
+        ));
+
+        // here they reach the crossing half way through the step, together
+        let on_time = missile(0.0, 100.0, enemy_velocity);
+        assert!(sweep_intersects(
+            &player,
+            player_velocity - enemy_velocity,
+            &on_time
+        ));
+    }
+
+    #[test]
+    fn sweep_against_building_at_any_speed() {
+        let building = rect(0.0, 500.0, 64.0, 64.0);
+        for speed in [1.0, 10.0, 100.0, 1000.0] {
+            let velocity = vec2(0.0, speed + 100.0);
+            assert!(
+                sweep_intersects(&missile(32.0, 600.0 + speed, velocity), velocity, &building),
+                "missed at speed {}",
+                speed
+            );
+        }
+    }
+
+    #[test]
+    fn sweep_without_motion_matches_intersects() {
+        let building = rect(0.0, 0.0, 64.0, 64.0);
+        let touching = rect(64.0, 0.0, 64.0, 64.0);
+        assert!(!sweep_intersects(&touching, Vec2::ZERO, &building));
+        assert!(sweep_intersects(&building, Vec2::ZERO, &building));
+        assert!(sweep_intersects(
+            &capsule((100.0, 30.0), (100.0, 34.0), 2.0),
+            vec2(40.0, 0.0),
+            &building
+        ));
+    }
+}
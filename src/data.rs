@@ -1,12 +1,22 @@
+use crate::animation::Animations;
+use crate::atlas::Atlas;
+use crate::ecs::{Collider, World};
+use crate::palette::Palettes;
+use crate::particles::Particles;
+use crate::rng::Rng;
 use macroquad::{audio, prelude::*};
 use rust_embed::RustEmbed;
 use serde::Deserialize;
 use std::rc::Rc;
+use string_tables::Strings;
 
 pub const CELL_SIZE: f32 = 32.0;
-pub const ENEMY_COLOR: Color = RED;
-pub const PLAYER_COLOR: Color = GREEN;
-pub const BUILDING_COLOR: Color = WHITE;
+pub const PLAYER_TRAIL_THICKNESS: f32 = 1.0;
+pub const ENEMY_TRAIL_THICKNESS: f32 = 1.5;
+/// Trails start a new segment when a missile turns by more than the angle
+/// with this cosine.
+pub const TRAIL_STRAIGHT_COS: f32 = 0.9999;
+pub const TRAIL_FADE_TICKS: u32 = 45;
 pub const MISSILE_SIZE: f32 = CELL_SIZE;
 pub const PLANE_WIDTH: f32 = CELL_SIZE;
 pub const PLANE_HEIGHT: f32 = 16.0;
@@ -14,103 +24,140 @@ pub const CANNON_BASE_WIDTH: f32 = CELL_SIZE;
 pub const CANNON_BASE_HEIGHT: f32 = CELL_SIZE;
 pub const CANNON_BARREL_WIDTH: f32 = CELL_SIZE;
 pub const CANNON_BARREL_HEIGHT: f32 = CELL_SIZE;
+pub const GROUND_TILE_SIZE: f32 = 8.0;
+pub const GROUND_TILES_PER_ROW: u8 = 4;
+pub const DEFAULT_LEVEL_WIDTH: f32 = 800.0;
+pub const DEFAULT_LEVEL_HEIGHT: f32 = 600.0;
+pub const DEFAULT_CANNON_AMMO: u32 = 10;
+pub const DEFAULT_PLANE_SPEED: f32 = 1.0;
+pub const TICKS_PER_SECOND: f32 = 60.0;
+pub const MIRV_SPREAD: f32 = 96.0;
+pub const WAVE_SPEED_STEP: f32 = 0.1;
+pub const PLAYER_MISSILE_SPEED: f32 = 2.5;
+/// How close a missile has to pass to something to hit it.
+pub const MISSILE_RADIUS: f32 = MISSILE_SIZE / 4.0;
+pub const MISSILE_COLLIDER: Collider = Collider::Capsule {
+    length: MISSILE_SIZE,
+    radius: MISSILE_RADIUS,
+};
 
 #[derive(RustEmbed)]
 #[folder = "assets/"]
 pub struct Assets;
 
-#[derive(Deserialize)]
-pub struct Entity {
-    pub x: f32,
-    pub y: f32,
-    pub id: u8,
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Warhead {
+    #[default]
+    Standard,
+    Mirv,
 }
 
-#[derive(Deserialize)]
-pub struct LevelData {
-    pub buildings: Vec<Entity>,
-    pub cannons: Vec<Entity>,
-    pub planes: Vec<Entity>,
-    pub enemy_missiles: Vec<Entity>,
-    pub ground: Vec<Entity>,
+/// An enemy missile fired from a spawnpoint `time` seconds after the wave starts.
+#[derive(Deserialize, Clone)]
+pub struct Launch {
+    pub time: f32,
+    pub target_x: f32,
+    pub target_y: f32,
+    pub speed: f32,
+    #[serde(default)]
+    pub warhead: Warhead,
 }
 
-#[derive(PartialEq)]
-pub struct Missile {
-    pub x: f32,
-    pub y: f32,
-    pub direction: Vec2,
-    pub speed: f32,
-    pub trail_length: f32,
-    pub should_destroy: bool,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitTarget {
+    Missile,
+    City,
+    Other,
 }
 
-impl Missile {
-    pub fn new(x: f32, y: f32, direction: Vec2, speed: f32) -> Missile {
-        Missile {
-            x,
-            y,
-            direction,
-            speed,
-            trail_length: 0.0,
-            should_destroy: false,
-        }
-    }
+/// Something that happened during a tick, for effects that react to the game
+/// such as particles. The list is cleared at the start of every tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    /// A missile at `position` hit a target with `target_bounds`.
+    Hit {
+        position: Vec2,
+        target: HitTarget,
+        target_bounds: Rect,
+    },
+    Detonation {
+        position: Vec2,
+    },
 }
 
-#[derive(PartialEq)]
-pub struct Building {
-    pub x: f32,
-    pub y: f32,
-    pub size: Vec2,
-    pub texture: Rc<Texture2D>,
-    pub should_destroy: bool,
+pub struct ScheduledLaunch {
+    pub tick: u32,
+    pub origin: Vec2,
+    pub launch: Launch,
 }
 
-#[derive(PartialEq)]
-pub struct Plane {
+#[derive(Deserialize)]
+pub struct Entity {
     pub x: f32,
     pub y: f32,
-    pub direction: Vec2,
-    pub speed: f32,
-    pub size: Vec2,
-    pub should_destroy: bool,
+    pub id: u8,
+    #[serde(default)]
+    pub ammo: Option<u32>,
+    #[serde(default)]
+    pub speed: Option<f32>,
+    #[serde(default)]
+    pub tile: Option<u8>,
+    #[serde(default)]
+    pub launches: Vec<Launch>,
 }
 
-#[derive(Clone, Copy)]
-pub struct Cannon {
-    pub x: f32,
-    pub y: f32,
-    pub target: Vec2,
-    pub ammo: u32,
+fn default_level_width() -> f32 {
+    DEFAULT_LEVEL_WIDTH
 }
 
-pub struct Crosshair {
-    pub x: f32,
-    pub y: f32,
-    pub should_destroy: bool,
-    pub missile_index: usize,
+fn default_level_height() -> f32 {
+    DEFAULT_LEVEL_HEIGHT
+}
+
+#[derive(Deserialize)]
+pub struct LevelData {
+    #[serde(default = "default_level_width")]
+    pub width: f32,
+    #[serde(default = "default_level_height")]
+    pub height: f32,
+    pub buildings: Vec<Entity>,
+    pub cannons: Vec<Entity>,
+    pub planes: Vec<Entity>,
+    pub enemy_missiles: Vec<Entity>,
+    pub ground: Vec<Entity>,
+    /// Seeds runs started without `--seed`. Without either, the clock is used.
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 pub struct Game {
-    pub buildings: Vec<Building>,
-    pub planes: Vec<Plane>,
+    pub world: World,
     pub enemy_missiles_spawnpoints: Vec<Entity>,
     pub ground_entities: Vec<Entity>,
-    pub enemy_missiles: Vec<Missile>,
-    pub player_missiles: Vec<Missile>,
-    pub cannons: Vec<Cannon>,
-    pub plane_texture: Rc<Texture2D>,
-    pub building_textures: Vec<Rc<Texture2D>>,
-    pub missile_texture: Rc<Texture2D>,
-    pub cannon_base_texture: Rc<Texture2D>,
-    pub cannon_barrel_texture: Rc<Texture2D>,
-    pub ground_texture: Rc<Texture2D>,
+    pub atlas: Rc<Atlas>,
     pub missile_fire_sound: Rc<audio::Sound>,
     pub explosion_sound: Rc<audio::Sound>,
     pub enemy_missile_sound: Rc<audio::Sound>,
-    pub crosshairs: Vec<Crosshair>,
+    pub animations: Animations,
+    pub font: Font,
+    pub strings: Strings,
+    pub palettes: Palettes,
+    pub aim_assist: bool,
     pub camera: Camera2D,
+    pub level_size: Vec2,
+    pub screen_size: Vec2,
     pub game_over: bool,
     pub score: i32,
+    pub wave: u32,
+    pub wave_tick: u32,
+    pub tick: u32,
+    /// Where the player aims, whatever device moves it.
+    pub aim: Vec2,
+    pub events: Vec<GameEvent>,
+    pub particles: Particles,
+    pub launch_queue: Vec<ScheduledLaunch>,
+    /// Seed `rng` started from, shown at game over to reproduce the run.
+    pub seed: u64,
+    pub rng: Rng,
 }
@@ -1,41 +1,80 @@
+use crate::animation::Frame;
+use crate::assist::AimAssist;
+use crate::atlas::Atlas;
 use crate::data::{
-    Building, Cannon, Crosshair, Entity, Game, Missile, Plane, BUILDING_COLOR,
-    CANNON_BARREL_HEIGHT, CANNON_BARREL_WIDTH, CANNON_BASE_HEIGHT, CANNON_BASE_WIDTH, ENEMY_COLOR,
-    MISSILE_SIZE, PLAYER_COLOR,
+    Entity, Game, CANNON_BARREL_HEIGHT, CANNON_BARREL_WIDTH, CANNON_BASE_WIDTH, CELL_SIZE,
+    GROUND_TILES_PER_ROW, GROUND_TILE_SIZE, TICKS_PER_SECOND, TRAIL_FADE_TICKS,
 };
+use crate::ecs::{Anchor, Components, Facing, Sprite, Trail, World};
+use crate::hud::{hud_scale, AmmoGauge, Hud};
+use crate::palette::Palette;
+use crate::particles::Particles;
+use crate::text::{draw_ui_text, measure_ui_text};
+use crate::ui::{options_rows, Button, Playback, FAST_FORWARD_SPEED};
 use macroquad::prelude::*;
 
-pub fn draw_missile(
-    x: f32,
-    y: f32,
-    size: f32,
-    direction: Vec2,
-    color: Color,
-    missile_texture: &Texture2D,
+/// Draws a sprite at an entity's position, turned the way its facing asks,
+/// showing `frame` of its animation in the colours of `palette`.
+pub fn draw_sprite(
+    position: Vec2,
+    velocity: Vec2,
+    sprite: &Sprite,
+    atlas: &Atlas,
+    frame: &Frame,
+    palette: &Palette,
 ) {
-    let rotation = direction.y.atan2(direction.x);
+    let top_left = match sprite.anchor {
+        Anchor::TopLeft => position,
+        Anchor::Center => position - sprite.size / 2.0,
+    };
+    // rotation is around the centre of the destination rect
+    let (rotation, flip_x) = match sprite.facing {
+        Facing::Fixed => (0.0, false),
+        Facing::Heading => (velocity.y.atan2(velocity.x), false),
+        Facing::FlipX => (0.0, velocity.x > 0.0),
+    };
+    let mut offset = Vec2::from(frame.offset);
+    if flip_x {
+        offset.x = -offset.x;
+    }
+    let top_left = top_left + Vec2::from_angle(rotation).rotate(offset);
+    let source = atlas.source(&sprite.name, frame.source_rect());
     draw_texture_ex(
-        missile_texture,
-        x,
-        y,
-        color,
+        &atlas.texture,
+        top_left.x,
+        top_left.y,
+        frame.tinted(palette.color(sprite.color)),
         DrawTextureParams {
-            dest_size: Some(vec2(size, size / 2.0)),
+            dest_size: Some(sprite.size),
+            source: Some(source),
             rotation,
+            flip_x,
             ..Default::default()
         },
     );
 }
 
-pub fn draw_trail(x: f32, y: f32, length: f32, direction: Vec2) {
-    draw_line(
-        x,
-        y,
-        x + direction.x * length,
-        y + direction.y * length,
-        1.0,
-        WHITE,
-    );
+pub fn draw_trail(trail: &Trail, palette: &Palette, alpha: f32) {
+    let color = palette.color(trail.color);
+    let color = Color {
+        a: color.a * alpha,
+        ..color
+    };
+    for segment in trail.points.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        draw_line(from.x, from.y, to.x, to.y, trail.thickness, color);
+    }
+}
+
+pub fn draw_trails(world: &World, palette: &Palette) {
+    for entity in world.values() {
+        if let Some(trail) = &entity.trail {
+            let alpha = entity
+                .lifetime
+                .map_or(1.0, |lifetime| lifetime.0 as f32 / TRAIL_FADE_TICKS as f32);
+            draw_trail(trail, palette, alpha);
+        }
+    }
 }
 
 pub fn draw_x_crosshair(x: f32, y: f32, size: f32, color: Color) {
@@ -43,226 +82,466 @@ pub fn draw_x_crosshair(x: f32, y: f32, size: f32, color: Color) {
     draw_line(x + size, y - size, x - size, y + size, 1.0, color);
 }
 
-pub fn draw_cannon(
-    x: f32,
-    y: f32,
-    target: Vec2,
-    ammo: u32,
-    base_texture: &Texture2D,
-    barrel_texture: &Texture2D,
-) {
+/// The barrel drawn over a launcher's base sprite. `frame` is the barrel's
+/// recoil.
+pub fn draw_launcher(x: f32, y: f32, target: Vec2, atlas: &Atlas, frame: &Frame) {
     let direction = target - vec2(x, y);
     let rotation = (direction.y.atan2(direction.x).to_degrees() + 45.0).to_radians();
+    let offset = Vec2::from_angle(rotation).rotate(Vec2::from(frame.offset));
+    let source = atlas.source("missile_launcher_part_2", frame.source_rect());
     draw_texture_ex(
-        base_texture,
-        x,
-        y,
-        WHITE,
-        DrawTextureParams {
-            dest_size: Some(vec2(CANNON_BASE_WIDTH, CANNON_BASE_HEIGHT)),
-            ..Default::default()
-        },
-    );
-    draw_texture_ex(
-        barrel_texture,
-        x + 8.0,
-        y - CANNON_BARREL_HEIGHT / 2.0,
-        WHITE,
+        &atlas.texture,
+        x + 8.0 + offset.x,
+        y - CANNON_BARREL_HEIGHT / 2.0 + offset.y,
+        frame.tinted(WHITE),
         DrawTextureParams {
             dest_size: Some(vec2(CANNON_BARREL_WIDTH, CANNON_BARREL_HEIGHT)),
+            source: Some(source),
             rotation,
             ..Default::default()
         },
     );
-    let mut ammo_text = format!("{}", ammo);
-    if ammo == 0 {
-        ammo_text = "OUT".to_string();
-    }
-    draw_text(&ammo_text, x - 10.0, y + 10.0, 20.0, WHITE);
 }
 
-pub fn draw_building(x: f32, y: f32, size: Vec2, color: Color, building_texture: &Texture2D) {
+pub fn draw_ground(x: f32, y: f32, size: f32, color: Color, atlas: &Atlas) {
     draw_texture_ex(
-        building_texture,
+        &atlas.texture,
         x,
         y,
         color,
         DrawTextureParams {
-            dest_size: Some(size),
+            dest_size: Some(vec2(size, size)),
+            source: Some(atlas.region("ground")),
             ..Default::default()
         },
     );
 }
 
-pub fn draw_ground(x: f32, y: f32, size: f32, color: Color, ground_texture: &Texture2D) {
+pub fn draw_ground_tile(x: f32, y: f32, tile: u8, color: Color, atlas: &Atlas) {
+    let tile = Rect::new(
+        (tile % GROUND_TILES_PER_ROW) as f32 * GROUND_TILE_SIZE,
+        (tile / GROUND_TILES_PER_ROW) as f32 * GROUND_TILE_SIZE,
+        GROUND_TILE_SIZE,
+        GROUND_TILE_SIZE,
+    );
     draw_texture_ex(
-        ground_texture,
+        &atlas.texture,
         x,
         y,
         color,
         DrawTextureParams {
-            dest_size: Some(vec2(size, size)),
+            dest_size: Some(vec2(CELL_SIZE, CELL_SIZE)),
+            source: Some(atlas.source("ground_tiles", Some(tile))),
             ..Default::default()
         },
     );
 }
 
-pub fn draw_ground_entities(ground_entities: &Vec<Entity>, ground_texture: &Texture2D) {
+pub fn draw_ground_entities(ground_entities: &Vec<Entity>, color: Color, atlas: &Atlas) {
     for ground_entity in ground_entities {
-        draw_ground(
-            ground_entity.x,
-            ground_entity.y,
-            50.0,
-            WHITE,
-            ground_texture,
-        );
+        match ground_entity.tile {
+            Some(tile) => draw_ground_tile(ground_entity.x, ground_entity.y, tile, color, atlas),
+            None => draw_ground(ground_entity.x, ground_entity.y, 50.0, color, atlas),
+        }
     }
 }
 
-pub fn draw_buildings(buildings: &Vec<Building>) {
-    for building in buildings {
-        draw_building(
-            building.x,
-            building.y,
-            building.size,
-            BUILDING_COLOR,
-            &building.texture,
+/// Draws every entity with a sprite, layer by layer, together with the
+/// trails and barrels that belong to it.
+pub fn draw_entities(game: &Game, palette: &Palette) {
+    let mut entities: Vec<&Components> = game
+        .world
+        .values()
+        .filter(|entity| entity.sprite.is_some())
+        .collect();
+    entities.sort_by_key(|entity| entity.sprite.as_ref().map(|sprite| sprite.layer));
+
+    for entity in entities {
+        let (Some(position), Some(sprite)) = (entity.position, &entity.sprite) else {
+            continue;
+        };
+        let velocity = entity.velocity();
+        draw_sprite(
+            position.0,
+            velocity,
+            sprite,
+            &game.atlas,
+            game.animations.frame(entity.animation.as_ref()),
+            palette,
         );
-        // draw_aabb(
-        //     building.x + building.size / 2.0,
-        //     building.y + building.size / 2.0,
-        //     building.size,
-        //     BLUE
-        // );
+        if let Some(launcher) = entity.launcher {
+            draw_launcher(
+                position.0.x,
+                position.0.y,
+                launcher.target,
+                &game.atlas,
+                game.animations.frame(Some(&launcher.barrel)),
+            );
+        }
     }
 }
 
-pub fn draw_planes(planes: &Vec<Plane>, plane_texture: &Texture2D) {
-    for plane in planes {
-        let should_flip = plane.direction.x > 0.0;
-        draw_texture_ex(
-            plane_texture,
-            plane.x,
-            plane.y,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(plane.size),
-                flip_x: should_flip,
-                ..Default::default()
-            },
-        );
+pub fn draw_crosshairs(world: &World, color: Color) {
+    for entity in world.values() {
+        if let (Some(position), Some(_)) = (entity.position, entity.crosshair) {
+            draw_x_crosshair(position.0.x, position.0.y, 10.0, color);
+        }
     }
 }
 
-pub fn draw_enemy_missiles(enemy_missiles: &Vec<Missile>, missile_texture: &Texture2D) {
-    for missile in enemy_missiles {
-        draw_trail(
-            missile.x + MISSILE_SIZE / 2.0,
-            missile.y,
-            missile.trail_length,
-            -missile.direction,
-        );
-        draw_missile(
-            missile.x,
-            missile.y,
-            MISSILE_SIZE,
-            missile.direction,
-            ENEMY_COLOR,
-            missile_texture,
+pub fn draw_particles(particles: &Particles) {
+    for particle in particles.iter() {
+        let position = particle.position;
+        draw_circle(
+            position.x,
+            position.y,
+            particle.size / 2.0,
+            particle.color(),
         );
-        //draw_aabb(missile.x, missile.y, MISSILE_SIZE, BLUE);
     }
 }
 
-pub fn draw_player_missiles(player_missiles: &Vec<Missile>, missile_texture: &Texture2D) {
-    for missile in player_missiles {
-        draw_trail(
-            missile.x + MISSILE_SIZE / 2.0,
-            missile.y,
-            missile.trail_length,
-            -missile.direction,
-        );
-        draw_missile(
-            missile.x,
-            missile.y,
-            MISSILE_SIZE,
-            missile.direction,
-            WHITE,
-            missile_texture,
+/// The shared aim point, a plus so it is not mistaken for a target marker.
+pub fn draw_aim(aim: Vec2) {
+    let size = 6.0;
+    draw_line(aim.x - size, aim.y, aim.x + size, aim.y, 1.0, WHITE);
+    draw_line(aim.x, aim.y - size, aim.x, aim.y + size, 1.0, WHITE);
+}
+
+pub fn draw_game(game: &Game) {
+    let palette = game.palettes.for_wave(game.wave);
+    draw_ground_entities(&game.ground_entities, palette.ground, &game.atlas);
+    draw_trails(&game.world, palette);
+    draw_entities(game, palette);
+    draw_particles(&game.particles);
+    draw_crosshairs(&game.world, palette.interceptor);
+    draw_aim(game.aim);
+    if game.game_over {
+        draw_game_over(game);
+    }
+}
+
+pub fn draw_flash(level_size: Vec2, alpha: f32) {
+    if alpha > 0.0 {
+        draw_rectangle(
+            0.0,
+            0.0,
+            level_size.x,
+            level_size.y,
+            Color::new(1.0, 1.0, 1.0, alpha * 0.8),
         );
-        //draw_aabb(missile.x, missile.y, MISSILE_SIZE, BLUE);
     }
 }
 
-pub fn draw_cannons(
-    cannons: &Vec<Cannon>,
-    cannon_base_texture: &Texture2D,
-    cannon_barrel_texture: &Texture2D,
-) {
-    for cannon in cannons {
-        draw_cannon(
-            cannon.x,
-            cannon.y,
-            cannon.target,
-            cannon.ammo,
-            cannon_base_texture,
-            cannon_barrel_texture,
+pub fn draw_game_over(game: &Game) {
+    draw_rectangle(
+        0.0,
+        0.0,
+        game.level_size.x,
+        game.level_size.y,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    let strings = &game.strings;
+    let lines = [
+        (strings.get("game_over.title"), 60.0),
+        (
+            strings.format("game_over.score", &[&game.score, &game.wave]),
+            30.0,
+        ),
+        (strings.format("game_over.seed", &[&game.seed]), 30.0),
+    ];
+    let mut y = game.level_size.y * 0.4;
+    for (text, font_size) in lines {
+        let size = measure_ui_text(&game.font, &text, font_size);
+        draw_ui_text(
+            &game.font,
+            &text,
+            (game.level_size.x - size.width) * 0.5,
+            y,
+            font_size,
+            WHITE,
         );
+        y += font_size + 10.0;
     }
 }
 
-pub fn draw_crosshairs(crosshairs: &Vec<Crosshair>) {
-    for crosshair in crosshairs {
-        if crosshair.should_destroy {
-            continue;
-        }
+pub fn draw_button(button: &Button, font: &Font) {
+    let rect = button.rect;
+    let fill = if button.active {
+        Color::new(1.0, 1.0, 1.0, 0.35)
+    } else {
+        Color::new(0.0, 0.0, 0.0, 0.5)
+    };
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, WHITE);
+    let size = measure_ui_text(font, button.label, 32.0);
+    draw_ui_text(
+        font,
+        button.label,
+        rect.center().x - size.width / 2.0,
+        rect.center().y + size.offset_y / 2.0,
+        32.0,
+        WHITE,
+    );
+}
 
-        draw_x_crosshair(crosshair.x, crosshair.y, 10.0, PLAYER_COLOR);
+pub fn draw_playback(game: &Game, playback: &Playback) {
+    for button in playback.buttons(game.level_size) {
+        draw_button(&button, &game.font);
+    }
+    if playback.options {
+        draw_options(game);
+    }
+    if !playback.replay {
+        return;
     }
-}
 
-pub fn draw_score(score: i32) {
-    let score_text = format!("Score: {}", score);
-    draw_text(&score_text, 10.0, 20.0, 30.0, WHITE);
+    let strings = &game.strings;
+    let speed = if playback.paused {
+        strings.get("replay.paused")
+    } else if playback.fast_forwarding() {
+        strings.format("replay.speed", &[&FAST_FORWARD_SPEED])
+    } else {
+        strings.format("replay.speed", &[&1])
+    };
+    let text = strings.format("replay.status", &[&game.tick, &speed]);
+    let size = measure_ui_text(&game.font, &text, 20.0);
+    draw_ui_text(
+        &game.font,
+        &text,
+        game.level_size.x - size.width - 10.0,
+        20.0,
+        20.0,
+        WHITE,
+    );
 }
 
-pub fn draw_game(game: &Game) {
-    draw_ground_entities(&game.ground_entities, &game.ground_texture);
-    draw_buildings(&game.buildings);
-    draw_planes(&game.planes, &game.plane_texture);
-    draw_cannons(
-        &game.cannons,
-        &game.cannon_base_texture,
-        &game.cannon_barrel_texture,
+pub fn draw_options(game: &Game) {
+    let (font, strings) = (&game.font, &game.strings);
+    draw_rectangle(
+        0.0,
+        0.0,
+        game.level_size.x,
+        game.level_size.y,
+        Color::new(0.0, 0.0, 0.0, 0.7),
     );
-    draw_crosshairs(&game.crosshairs);
-    draw_enemy_missiles(&game.enemy_missiles, &game.missile_texture);
-    draw_player_missiles(&game.player_missiles, &game.missile_texture);
-    draw_score(game.score);
+    let title = strings.get("options.title");
+    let size = measure_ui_text(font, &title, 48.0);
+    draw_ui_text(
+        font,
+        &title,
+        (game.level_size.x - size.width) * 0.5,
+        game.level_size.y * 0.3,
+        48.0,
+        WHITE,
+    );
+
+    let labels = [
+        strings.format("options.language", &[&strings.language_name()]),
+        strings.format(
+            "options.palette",
+            &[&strings.get(&format!("palette.{}", game.palettes.preset()))],
+        ),
+        strings.format(
+            "options.aim_assist",
+            &[&strings.get(if game.aim_assist {
+                "options.on"
+            } else {
+                "options.off"
+            })],
+        ),
+        strings.get("options.back"),
+    ];
+    for (rect, label) in options_rows(game.level_size).iter().zip(labels) {
+        draw_rectangle(
+            rect.x,
+            rect.y,
+            rect.w,
+            rect.h,
+            Color::new(0.0, 0.0, 0.0, 0.5),
+        );
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, WHITE);
+        let size = measure_ui_text(font, &label, 28.0);
+        draw_ui_text(
+            font,
+            &label,
+            rect.center().x - size.width / 2.0,
+            rect.center().y + size.offset_y / 2.0,
+            28.0,
+            WHITE,
+        );
+    }
 }
 
-#[allow(dead_code)]
-pub fn draw_aabb(x: f32, y: f32, size: f32, color: Color) {
-    draw_rectangle_lines(x - size / 2.0, y - size / 2.0, size, size, 2.0, color);
+/// A ring on a missile's path marks where it will be when the quickest shot
+/// reaches the aim point, so a shot hits when the ring is inside the circle.
+pub fn draw_aim_assist(game: &Game, assist: &AimAssist) {
+    let palette = game.palettes.for_wave(game.wave);
+    let faded = |color: Color, alpha: f32| Color { a: alpha, ..color };
+    let seconds = |ticks: u32| {
+        let seconds = format!("{:.1}", ticks as f32 / TICKS_PER_SECOND);
+        game.strings.format("assist.seconds", &[&seconds])
+    };
+
+    for projection in &assist.projections {
+        let (from, to) = (projection.position, projection.end);
+        draw_line(from.x, from.y, to.x, to.y, 1.0, faded(palette.enemy, 0.4));
+        if let Some(intercept) = projection.intercept {
+            draw_circle_lines(intercept.x, intercept.y, 4.0, 1.0, palette.interceptor);
+        }
+    }
+
+    for threat in &assist.threats {
+        let text = seconds(threat.ticks);
+        let size = measure_ui_text(&game.font, &text, 16.0);
+        draw_ui_text(
+            &game.font,
+            &text,
+            threat.position.x - size.width / 2.0,
+            threat.position.y - 6.0,
+            16.0,
+            palette.hud_warning,
+        );
+    }
+
+    for shot in &assist.shots {
+        let (from, to) = (shot.origin, shot.aim);
+        draw_line(
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            1.0,
+            faded(palette.interceptor, 0.3),
+        );
+    }
+    // every shot goes to the aim point, label the one that gets there first
+    if let Some(shot) = assist.shots.iter().min_by_key(|shot| shot.ticks) {
+        let to = shot.aim;
+        draw_circle_lines(to.x, to.y, shot.radius, 1.0, palette.interceptor);
+        let label = game
+            .strings
+            .format("assist.hit_radius", &[&seconds(shot.ticks)]);
+        draw_ui_text(
+            &game.font,
+            &label,
+            to.x + shot.radius + 4.0,
+            to.y - shot.radius,
+            16.0,
+            palette.interceptor,
+        );
+    }
 }
 
-pub fn draw_background(texture: &Texture2D) {
-    // draw texture centered on screen and scale it to screen size (without changing aspect ratio)
-    let screen_width = screen_width();
-    let screen_height = screen_height();
+pub fn draw_background(texture: &Texture2D, level_size: Vec2, tint: Color) {
+    // draw texture centered on the level canvas and scale it to cover the whole canvas (without changing aspect ratio)
     let texture_width = texture.width();
     let texture_height = texture.height();
-    let scale = screen_width / texture_width;
-    let x = screen_width / 2.0 - (texture_width / 2.0) * scale;
-    let y = screen_height / 2.0 - (texture_height / 2.0) * scale;
+    let scale = (level_size.x / texture_width).max(level_size.y / texture_height);
+    let x = level_size.x / 2.0 - (texture_width / 2.0) * scale;
+    let y = level_size.y / 2.0 - (texture_height / 2.0) * scale;
     draw_texture_ex(
         texture,
         x,
         y,
-        WHITE,
+        tint,
         DrawTextureParams {
             dest_size: Some(vec2(texture_width * scale, texture_height * scale)),
             ..Default::default()
         },
     );
 }
+
+pub fn draw_ammo_gauge(game: &Game, gauge: &AmmoGauge, scale: f32) {
+    let palette = game.palettes.for_wave(game.wave);
+    let camera = &game.camera;
+    let above = gauge.position + vec2(CANNON_BASE_WIDTH / 2.0, -CANNON_BARREL_HEIGHT);
+    let center = camera.world_to_screen(above);
+    let (width, height) = (40.0 * scale, 6.0 * scale);
+    let (x, y) = (center.x - width / 2.0, center.y - height);
+    if gauge.ammo == 0 {
+        let text = game.strings.get("hud.out_of_ammo");
+        let size = measure_ui_text(&game.font, &text, 16.0 * scale);
+        draw_ui_text(
+            &game.font,
+            &text,
+            center.x - size.width / 2.0,
+            y + height,
+            16.0 * scale,
+            palette.hud_warning,
+        );
+        return;
+    }
+
+    let full = gauge.ammo as f32 / gauge.max_ammo.max(1) as f32;
+    let color = if full > 0.25 {
+        palette.hud
+    } else {
+        palette.hud_warning
+    };
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.6));
+    draw_rectangle(x, y, width * full, height, color);
+    draw_rectangle_lines(x, y, width, height, 1.0, WHITE);
+}
+
+/// Score, wave and what is left to defend, drawn in window pixels after the
+/// level canvas so that it stays put when the camera shakes.
+pub fn draw_hud(game: &Game, hud: &Hud) {
+    set_default_camera();
+    let scale = hud_scale(vec2(screen_width(), screen_height()), game.level_size);
+    let (margin, font_size) = (10.0 * scale, 24.0 * scale);
+    let line = font_size + 4.0 * scale;
+
+    let (font, strings) = (&game.font, &game.strings);
+    let palette = game.palettes.for_wave(game.wave);
+    draw_ui_text(
+        font,
+        &strings.format("hud.score", &[&hud.score]),
+        margin,
+        margin + font_size,
+        font_size,
+        palette.hud,
+    );
+    draw_ui_text(
+        font,
+        &strings.format("hud.high_score", &[&hud.high_score]),
+        margin,
+        margin + font_size + line,
+        font_size,
+        Color {
+            a: 0.6,
+            ..palette.hud
+        },
+    );
+
+    let wave = strings.format("hud.wave", &[&hud.wave, &hud.multiplier]);
+    let status = strings.format("hud.status", &[&hud.cities, &hud.incoming]);
+    for (index, (text, color)) in [
+        (wave, palette.hud),
+        (
+            status,
+            if hud.incoming > 0 {
+                palette.hud_warning
+            } else {
+                palette.hud
+            },
+        ),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let size = measure_ui_text(font, &text, font_size);
+        draw_ui_text(
+            font,
+            &text,
+            (screen_width() - size.width) / 2.0,
+            margin + font_size + line * index as f32,
+            font_size,
+            color,
+        );
+    }
+
+    for gauge in &hud.gauges {
+        draw_ammo_gauge(game, gauge, scale);
+    }
+}
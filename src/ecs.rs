@@ -0,0 +1,201 @@
+use crate::animation::AnimationState;
+use crate::arena::{Arena, Id};
+use crate::data::Warhead;
+use crate::palette::PaletteColor;
+use macroquad::prelude::*;
+
+pub type EntityId = Id<Components>;
+
+/// World position. Where on the entity it is anchored depends on the
+/// collider and sprite: rects use the top-left corner, the rest the centre.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position(pub Vec2);
+
+/// Distance moved every step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Velocity(pub Vec2);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Collider {
+    Rect {
+        size: Vec2,
+    },
+    /// Oriented along the velocity, `length` includes the rounded ends.
+    Capsule {
+        length: f32,
+        radius: f32,
+    },
+}
+
+/// Draw order, lowest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Buildings,
+    Planes,
+    Launchers,
+    Missiles,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Center,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Facing {
+    Fixed,
+    /// Rotated to point along the velocity.
+    Heading,
+    /// Mirrored when moving right.
+    FlipX,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sprite {
+    /// Name of the sprite in the atlas.
+    pub name: String,
+    pub size: Vec2,
+    pub color: PaletteColor,
+    pub layer: Layer,
+    pub anchor: Anchor,
+    pub facing: Facing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Health(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Team {
+    Player,
+    Enemy,
+    Neutral,
+}
+
+impl Team {
+    /// Which teams' entities a missile of this team destroys on contact.
+    pub fn hits(self, other: Team) -> bool {
+        matches!(
+            (self, other),
+            (Team::Player, Team::Enemy)
+                | (Team::Player, Team::Neutral)
+                | (Team::Enemy, Team::Player)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lifetime(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Missile {
+    pub trail_length: f32,
+    pub target: Option<Vec2>,
+    pub warhead: Warhead,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Launcher {
+    pub target: Vec2,
+    pub ammo: u32,
+    pub max_ammo: u32,
+    /// Recoil of the barrel, restarted on every shot.
+    pub barrel: AnimationState,
+}
+
+/// Path a missile flew. Outlives the missile, fading out over its lifetime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trail {
+    pub missile: EntityId,
+    pub points: Vec<Vec2>,
+    pub color: PaletteColor,
+    pub thickness: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crosshair {
+    pub missile: EntityId,
+}
+
+/// The game is over once none are left.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct City;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reward(pub i32);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Components {
+    pub position: Option<Position>,
+    pub velocity: Option<Velocity>,
+    pub collider: Option<Collider>,
+    pub sprite: Option<Sprite>,
+    pub animation: Option<AnimationState>,
+    pub health: Option<Health>,
+    pub team: Option<Team>,
+    pub lifetime: Option<Lifetime>,
+    pub missile: Option<Missile>,
+    pub launcher: Option<Launcher>,
+    pub trail: Option<Trail>,
+    pub crosshair: Option<Crosshair>,
+    pub city: Option<City>,
+    pub reward: Option<Reward>,
+}
+
+impl Components {
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity.map_or(Vec2::ZERO, |velocity| velocity.0)
+    }
+
+    /// Entities without health live until something else removes them.
+    pub fn is_alive(&self) -> bool {
+        self.health.is_none_or(|health| health.0 > 0)
+    }
+}
+
+#[derive(Default)]
+pub struct World {
+    entities: Arena<Components>,
+}
+
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+
+    pub fn spawn(&mut self, components: Components) -> EntityId {
+        self.entities.insert(components)
+    }
+
+    pub fn despawn(&mut self, id: EntityId) -> Option<Components> {
+        self.entities.remove(id)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Components> {
+        self.entities.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Components> {
+        self.entities.get_mut(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Components)> {
+        self.entities.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut Components)> {
+        self.entities.iter_mut()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Components> {
+        self.entities.values()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Components> {
+        self.entities.values_mut()
+    }
+
+    pub fn retain(&mut self, keep: impl FnMut(&Components) -> bool) {
+        self.entities.retain(keep);
+    }
+}
@@ -0,0 +1,121 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Sticks report small values when left alone, anything below is ignored.
+#[cfg(feature = "gamepad")]
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+/// Gamepad buttons by their position, so bindings work across controller
+/// layouts. `South` is A on an Xbox pad and cross on a PlayStation one.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+}
+
+#[derive(Debug, Default)]
+pub struct GamepadState {
+    /// Left stick or d-pad, y pointing down like the screen.
+    pub stick: Vec2,
+    pub pressed: Vec<GamepadButton>,
+}
+
+impl GamepadState {
+    pub fn any_pressed(&self, buttons: &[GamepadButton]) -> bool {
+        buttons.iter().any(|button| self.pressed.contains(button))
+    }
+}
+
+/// Reads every connected gamepad. Without the `gamepad` feature there are
+/// never any, so the game builds without the platform's gamepad libraries.
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Gamepads::new()
+    }
+}
+
+impl Gamepads {
+    #[cfg(feature = "gamepad")]
+    pub fn new() -> Gamepads {
+        Gamepads {
+            gilrs: gilrs::Gilrs::new().ok(),
+        }
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn new() -> Gamepads {
+        Gamepads {}
+    }
+
+    #[cfg(feature = "gamepad")]
+    pub fn poll(&mut self) -> GamepadState {
+        use gilrs::{Axis, Button, EventType};
+
+        let mut state = GamepadState::default();
+        let Some(gilrs) = &mut self.gilrs else {
+            return state;
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            let EventType::ButtonPressed(button, _) = event.event else {
+                continue;
+            };
+            let button = match button {
+                Button::South => GamepadButton::South,
+                Button::East => GamepadButton::East,
+                Button::North => GamepadButton::North,
+                Button::West => GamepadButton::West,
+                Button::LeftTrigger => GamepadButton::LeftBumper,
+                Button::RightTrigger => GamepadButton::RightBumper,
+                Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+                Button::RightTrigger2 => GamepadButton::RightTrigger,
+                Button::Select => GamepadButton::Select,
+                Button::Start => GamepadButton::Start,
+                _ => continue,
+            };
+            state.pressed.push(button);
+        }
+
+        for (_, gamepad) in gilrs.gamepads() {
+            let stick = vec2(
+                gamepad.value(Axis::LeftStickX),
+                -gamepad.value(Axis::LeftStickY),
+            );
+            if stick.length() > STICK_DEAD_ZONE {
+                state.stick += stick;
+            }
+            let d_pad = [
+                (Button::DPadLeft, vec2(-1.0, 0.0)),
+                (Button::DPadRight, vec2(1.0, 0.0)),
+                (Button::DPadUp, vec2(0.0, -1.0)),
+                (Button::DPadDown, vec2(0.0, 1.0)),
+            ];
+            for (button, direction) in d_pad {
+                if gamepad.is_pressed(button) {
+                    state.stick += direction;
+                }
+            }
+        }
+        state.stick = state.stick.clamp_length_max(1.0);
+
+        state
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn poll(&mut self) -> GamepadState {
+        GamepadState::default()
+    }
+}
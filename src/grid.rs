@@ -0,0 +1,166 @@
+use crate::data::CELL_SIZE;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+/// Uniform grid broadphase, rebuilt every step from the boxes entities
+/// cover during that step.
+pub struct SpatialGrid<K = usize> {
+    cells: HashMap<(i32, i32), Vec<K>>,
+}
+
+fn cell_range(bounds: Rect) -> (i32, i32, i32, i32) {
+    (
+        (bounds.x / CELL_SIZE).floor() as i32,
+        (bounds.y / CELL_SIZE).floor() as i32,
+        ((bounds.x + bounds.w) / CELL_SIZE).floor() as i32,
+        ((bounds.y + bounds.h) / CELL_SIZE).floor() as i32,
+    )
+}
+
+impl<K: Copy + Ord> SpatialGrid<K> {
+    pub fn new() -> SpatialGrid<K> {
+        SpatialGrid {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, key: K, bounds: Rect) {
+        let (left, top, right, bottom) = cell_range(bounds);
+        for x in left..=right {
+            for y in top..=bottom {
+                self.cells.entry((x, y)).or_default().push(key);
+            }
+        }
+    }
+
+    /// Candidates near `bounds`, in ascending order and without duplicates.
+    pub fn query(&self, bounds: Rect) -> Vec<K> {
+        let (left, top, right, bottom) = cell_range(bounds);
+        let mut found = vec![];
+        for x in left..=right {
+            for y in top..=bottom {
+                if let Some(keys) = self.cells.get(&(x, y)) {
+                    found.extend_from_slice(keys);
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Warhead, MISSILE_COLLIDER};
+    use crate::ecs::{Components, Health, Missile, Position, Team, Velocity};
+    use crate::logic::{can_hit, entities_hit, entity_bounds};
+    use std::time::Instant;
+
+    fn missiles(count: usize, direction: Vec2, team: Team, seed: u32) -> Vec<Components> {
+        let mut state = seed;
+        let mut next = move |range: f32| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1 << 24) as f32 * range
+        };
+        (0..count)
+            .map(|_| Components {
+                position: Some(Position(vec2(next(1600.0), next(1200.0)))),
+                velocity: Some(Velocity(direction * (1.0 + next(4.0)))),
+                collider: Some(MISSILE_COLLIDER),
+                health: Some(Health(1)),
+                team: Some(team),
+                missile: Some(Missile {
+                    trail_length: 0.0,
+                    target: None,
+                    warhead: Warhead::Standard,
+                }),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn hits(player: &Components, enemy: &Components) -> bool {
+        can_hit(player, enemy) && entities_hit(player, enemy)
+    }
+
+    fn brute_force_hits(players: &[Components], enemies: &[Components]) -> Vec<(usize, usize)> {
+        let mut found = vec![];
+        for (i, player) in players.iter().enumerate() {
+            for (j, enemy) in enemies.iter().enumerate() {
+                if hits(player, enemy) {
+                    found.push((i, j));
+                }
+            }
+        }
+
+        found
+    }
+
+    fn grid_hits(players: &[Components], enemies: &[Components]) -> Vec<(usize, usize)> {
+        let mut grid = SpatialGrid::new();
+        for (index, enemy) in enemies.iter().enumerate() {
+            grid.insert(index, entity_bounds(enemy).unwrap());
+        }
+
+        let mut found = vec![];
+        for (i, player) in players.iter().enumerate() {
+            for j in grid.query(entity_bounds(player).unwrap()) {
+                if hits(player, &enemies[j]) {
+                    found.push((i, j));
+                }
+            }
+        }
+
+        found
+    }
+
+    #[test]
+    fn query_finds_neighbours_only() {
+        let mut grid = SpatialGrid::new();
+        grid.insert(0, Rect::new(10.0, 10.0, 8.0, 8.0));
+        grid.insert(1, Rect::new(500.0, 500.0, 8.0, 8.0));
+        grid.insert(2, Rect::new(-40.0, 0.0, 100.0, 8.0));
+
+        assert_eq!(grid.query(Rect::new(0.0, 0.0, 20.0, 20.0)), vec![0, 2]);
+        assert_eq!(grid.query(Rect::new(490.0, 490.0, 4.0, 4.0)), vec![1]);
+        assert!(grid.query(Rect::new(200.0, 200.0, 4.0, 4.0)).is_empty());
+    }
+
+    #[test]
+    fn grid_finds_the_same_hits_as_brute_force() {
+        let players = missiles(300, vec2(0.0, -1.0), Team::Player, 1);
+        let enemies = missiles(300, vec2(0.6, 0.8), Team::Enemy, 2);
+        let hits = brute_force_hits(&players, &enemies);
+        assert!(!hits.is_empty());
+        assert_eq!(grid_hits(&players, &enemies), hits);
+    }
+
+    /// Run with `cargo test --release grid_benchmark -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn grid_benchmark() {
+        for count in [100, 500, 2000] {
+            let players = missiles(count, vec2(0.0, -1.0), Team::Player, 1);
+            let enemies = missiles(count, vec2(0.6, 0.8), Team::Enemy, 2);
+
+            let start = Instant::now();
+            let brute_force = brute_force_hits(&players, &enemies);
+            let brute_force_time = start.elapsed();
+
+            let start = Instant::now();
+            let grid = grid_hits(&players, &enemies);
+            let grid_time = start.elapsed();
+
+            assert_eq!(grid, brute_force);
+            println!(
+                "{count} vs {count} missiles: brute force {:?}, grid {:?} ({:.1}x)",
+                brute_force_time,
+                grid_time,
+                brute_force_time.as_secs_f64() / grid_time.as_secs_f64()
+            );
+        }
+    }
+}
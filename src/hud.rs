@@ -0,0 +1,157 @@
+use crate::data::Game;
+use crate::ecs::Team;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+
+pub const HIGH_SCORE_PATH: &str = "highscore.json";
+/// HUD sizes are in pixels at this scale, which is a window the size of the
+/// level. Smaller windows never shrink the HUD below `MIN_HUD_SCALE`.
+const MIN_HUD_SCALE: f32 = 0.75;
+
+/// Best score of any live run, kept next to the game.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct HighScore {
+    pub score: i32,
+}
+
+impl HighScore {
+    /// No high score yet when the file is missing.
+    pub fn load(path: &str) -> Result<HighScore, String> {
+        match fs::read(path) {
+            Ok(data) => {
+                serde_json::from_slice(&data).map_err(|error| format!("{}: {}", path, error))
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(HighScore::default()),
+            Err(error) => Err(format!("{}: {}", path, error)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let data = serde_json::to_vec(self).map_err(|error| error.to_string())?;
+        fs::write(path, data).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    /// Takes `score` if it is better. Returns whether it was.
+    pub fn beat(&mut self, score: i32) -> bool {
+        let beaten = score > self.score;
+        if beaten {
+            self.score = score;
+        }
+        beaten
+    }
+}
+
+/// `position` is in level coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmmoGauge {
+    pub position: Vec2,
+    pub ammo: u32,
+    pub max_ammo: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hud {
+    pub score: i32,
+    pub high_score: i32,
+    pub wave: u32,
+    pub multiplier: i32,
+    pub cities: usize,
+    /// Enemy missiles in flight or still to be launched this wave.
+    pub incoming: usize,
+    pub gauges: Vec<AmmoGauge>,
+}
+
+impl Hud {
+    pub fn new(game: &Game, high_score: &HighScore) -> Hud {
+        let world = &game.world;
+        let in_flight = world
+            .values()
+            .filter(|entity| entity.missile.is_some() && entity.team == Some(Team::Enemy))
+            .count();
+        Hud {
+            score: game.score,
+            high_score: high_score.score.max(game.score),
+            wave: game.wave,
+            multiplier: score_multiplier(game.wave),
+            cities: world
+                .values()
+                .filter(|entity| entity.city.is_some())
+                .count(),
+            incoming: in_flight + game.launch_queue.len(),
+            gauges: world
+                .values()
+                .filter_map(|entity| {
+                    let launcher = entity.launcher?;
+                    Some(AmmoGauge {
+                        position: entity.position?.0,
+                        ammo: launcher.ammo,
+                        max_ammo: launcher.max_ammo,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Points are worth more in later waves: x1 for the first two, one more
+/// every two waves after, up to x6 like the arcade game.
+pub fn score_multiplier(wave: u32) -> i32 {
+    ((wave.max(1) - 1) / 2 + 1).min(6) as i32
+}
+
+/// A reward scored in `wave`. Penalties are not multiplied.
+pub fn scaled_reward(reward: i32, wave: u32) -> i32 {
+    if reward > 0 {
+        reward * score_multiplier(wave)
+    } else {
+        reward
+    }
+}
+
+pub fn hud_scale(screen_size: Vec2, level_size: Vec2) -> f32 {
+    let scale = (screen_size.x / level_size.x).min(screen_size.y / level_size.y);
+    scale.max(MIN_HUD_SCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplier_grows_every_other_wave_up_to_six() {
+        let multipliers: Vec<_> = (0..=14).map(score_multiplier).collect();
+        assert_eq!(multipliers, [1, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 6, 6]);
+    }
+
+    #[test]
+    fn only_points_are_multiplied_not_penalties() {
+        assert_eq!(scaled_reward(1, 11), 6);
+        assert_eq!(scaled_reward(-10, 11), -10);
+        assert_eq!(scaled_reward(0, 11), 0);
+    }
+
+    #[test]
+    fn high_scores_only_go_up_and_survive_a_restart() {
+        let path = std::env::temp_dir().join("missile_commander_high_score_test.json");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+        let mut high_score = HighScore::load(path).unwrap();
+        assert_eq!(high_score.score, 0);
+
+        assert!(high_score.beat(500));
+        assert!(!high_score.beat(300));
+        high_score.save(path).unwrap();
+        assert_eq!(HighScore::load(path).unwrap().score, 500);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn hud_keeps_a_readable_size_in_small_windows() {
+        let level_size = vec2(800.0, 600.0);
+        assert_eq!(hud_scale(vec2(1600.0, 1200.0), level_size), 2.0);
+        assert_eq!(hud_scale(vec2(1600.0, 600.0), level_size), 1.0);
+        assert_eq!(hud_scale(vec2(200.0, 150.0), level_size), MIN_HUD_SCALE);
+    }
+}
@@ -0,0 +1,497 @@
+use crate::data::Game;
+use crate::ecs::World;
+use crate::gamepad::{GamepadButton, Gamepads};
+use crate::logic::{get_closest_launcher, launcher_ids};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+
+/// `launcher` is the launcher's place among all launchers in the level, see
+/// [`launcher_ids`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fire {
+    pub launcher: usize,
+    pub aim: Vec2,
+}
+
+/// Everything the player did during one tick, in level coordinates, so that
+/// the same input plays out the same way whatever the window size.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TickInput {
+    pub aim: Vec2,
+    pub fires: Vec<Fire>,
+}
+
+/// How the player picks the launcher a shot comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlScheme {
+    #[default]
+    Closest,
+    Keys,
+}
+
+impl ControlScheme {
+    pub fn from_name(name: &str) -> Option<ControlScheme> {
+        match name {
+            "closest" => Some(ControlScheme::Closest),
+            "keys" => Some(ControlScheme::Keys),
+            _ => None,
+        }
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 54] = [
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("LeftShift", KeyCode::LeftShift),
+    ("RightShift", KeyCode::RightShift),
+    ("LeftControl", KeyCode::LeftControl),
+    ("RightControl", KeyCode::RightControl),
+    ("LeftAlt", KeyCode::LeftAlt),
+    ("RightAlt", KeyCode::RightAlt),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash),
+    ("Semicolon", KeyCode::Semicolon),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key(pub KeyCode);
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Key, String> {
+        KEY_NAMES
+            .iter()
+            .find(|(key_name, _)| *key_name == name)
+            .map(|(_, code)| Key(*code))
+            .ok_or_else(|| format!("unknown key: {}", name))
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> String {
+        KEY_NAMES
+            .iter()
+            .find(|(_, code)| *code == key.0)
+            .map_or_else(|| format!("{:?}", key.0), |(name, _)| name.to_string())
+    }
+}
+
+fn keys(names: &[&str]) -> Vec<Key> {
+    names
+        .iter()
+        .map(|name| Key::try_from(name.to_string()).unwrap())
+        .collect()
+}
+
+fn any_key_down(keys: &[Key]) -> bool {
+    keys.iter().any(|key| is_key_down(key.0))
+}
+
+fn any_key_pressed(keys: &[Key]) -> bool {
+    keys.iter().any(|key| is_key_pressed(key.0))
+}
+
+/// Which keys and gamepad buttons do what. Loaded from `INPUT_MAP_PATH` when
+/// it exists, anything it leaves out keeps its default binding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct InputMap {
+    pub cursor_up: Vec<Key>,
+    pub cursor_down: Vec<Key>,
+    pub cursor_left: Vec<Key>,
+    pub cursor_right: Vec<Key>,
+    /// Fires from the launcher closest to the aim point, `Closest` controls only.
+    pub fire_closest: Vec<Key>,
+    pub gamepad_fire_closest: Vec<GamepadButton>,
+    /// Bindings of the first, second, third... launcher, `Keys` controls only.
+    pub fire_launcher: Vec<Vec<Key>>,
+    pub gamepad_fire_launcher: Vec<Vec<GamepadButton>>,
+}
+
+pub const INPUT_MAP_PATH: &str = "input.json";
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap {
+            cursor_up: keys(&["Up"]),
+            cursor_down: keys(&["Down"]),
+            cursor_left: keys(&["Left"]),
+            cursor_right: keys(&["Right"]),
+            fire_closest: keys(&["Space"]),
+            gamepad_fire_closest: vec![GamepadButton::South, GamepadButton::RightTrigger],
+            fire_launcher: vec![
+                keys(&["A", "1"]),
+                keys(&["S", "2"]),
+                keys(&["D", "3"]),
+                keys(&["4"]),
+                keys(&["5"]),
+                keys(&["6"]),
+                keys(&["7"]),
+                keys(&["8"]),
+                keys(&["9"]),
+            ],
+            gamepad_fire_launcher: vec![
+                vec![GamepadButton::West],
+                vec![GamepadButton::South],
+                vec![GamepadButton::East],
+            ],
+        }
+    }
+}
+
+impl InputMap {
+    pub fn load(path: &str) -> Result<InputMap, String> {
+        match fs::read(path) {
+            Ok(data) => {
+                serde_json::from_slice(&data).map_err(|error| format!("{}: {}", path, error))
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(InputMap::default()),
+            Err(error) => Err(format!("{}: {}", path, error)),
+        }
+    }
+}
+
+/// Speeds of the keyboard and gamepad cursor, in level units per tick. Held
+/// down, it starts slow for precise aiming and speeds up to cross the level.
+const CURSOR_START_SPEED: f32 = 1.5;
+const CURSOR_MAX_SPEED: f32 = 12.0;
+const CURSOR_ACCELERATION: f32 = 0.4;
+
+#[derive(Debug, Default)]
+pub struct VirtualCursor {
+    pub position: Vec2,
+    speed: f32,
+}
+
+impl VirtualCursor {
+    /// Moves along `direction`, whose length scales the speed so that a
+    /// half-tilted stick moves half as fast, and stays inside the level.
+    pub fn steer(&mut self, direction: Vec2, level_size: Vec2) {
+        if direction == Vec2::ZERO {
+            self.speed = 0.0;
+            return;
+        }
+
+        self.speed = if self.speed == 0.0 {
+            CURSOR_START_SPEED
+        } else {
+            (self.speed + CURSOR_ACCELERATION).min(CURSOR_MAX_SPEED)
+        };
+        let direction = direction.clamp_length_max(1.0);
+        self.position = (self.position + direction * self.speed).clamp(Vec2::ZERO, level_size);
+    }
+}
+
+/// Where `update_game` gets its input from: the player's devices, or a
+/// recording when a replay is played back.
+pub trait InputSource {
+    fn poll(&mut self, game: &Game) -> TickInput;
+}
+
+/// Where touches come from, so taps can be tested without a device.
+pub trait TouchSource {
+    /// Touches of the current frame, in screen pixels.
+    fn touches(&mut self) -> Vec<Touch>;
+}
+
+pub struct ScreenTouches;
+
+impl TouchSource for ScreenTouches {
+    fn touches(&mut self) -> Vec<Touch> {
+        touches()
+    }
+}
+
+/// Positions of the touches that started this frame, ordered by touch id so
+/// that simultaneous taps always fire in the same order.
+pub fn taps(mut touches: Vec<Touch>) -> Vec<Vec2> {
+    touches.retain(|touch| touch.phase == TouchPhase::Started);
+    touches.sort_by_key(|touch| touch.id);
+    touches.into_iter().map(|touch| touch.position).collect()
+}
+
+/// Clicks and taps of this frame, in level coordinates. Used for buttons.
+pub fn pointer_presses(camera: &Camera2D) -> Vec<Vec2> {
+    let mut presses = taps(touches());
+    if is_mouse_button_pressed(MouseButton::Left) {
+        presses.push(Vec2::from(mouse_position()));
+    }
+    presses
+        .into_iter()
+        .map(|press| camera.screen_to_world(press))
+        .collect()
+}
+
+pub fn fire_from_closest(world: &World, aim: Vec2) -> Option<Fire> {
+    let closest = get_closest_launcher(world, aim)?;
+    let launcher = launcher_ids(world).iter().position(|id| *id == closest)?;
+    Some(Fire { launcher, aim })
+}
+
+/// The mouse puts the aim point under the cursor whenever it moves, the keys
+/// and sticks steer it from wherever it is. Presses are kept from the frame
+/// they happen on until the next tick, so that none are lost on frames that
+/// run no tick.
+pub struct PlayerInput {
+    pub controls: ControlScheme,
+    pub map: InputMap,
+    cursor: VirtualCursor,
+    gamepads: Gamepads,
+    touches: Box<dyn TouchSource>,
+    last_mouse_position: Option<Vec2>,
+    stick: Vec2,
+    fires: Vec<Fire>,
+}
+
+impl PlayerInput {
+    pub fn new(controls: ControlScheme, map: InputMap) -> PlayerInput {
+        PlayerInput::with_touches(controls, map, Box::new(ScreenTouches))
+    }
+
+    pub fn with_touches(
+        controls: ControlScheme,
+        map: InputMap,
+        touches: Box<dyn TouchSource>,
+    ) -> PlayerInput {
+        PlayerInput {
+            controls,
+            map,
+            cursor: VirtualCursor::default(),
+            gamepads: Gamepads::new(),
+            touches,
+            last_mouse_position: None,
+            stick: Vec2::ZERO,
+            fires: vec![],
+        }
+    }
+
+    /// Called once per frame, whether a tick runs or not.
+    pub fn collect(&mut self, game: &Game) {
+        let gamepad = self.gamepads.poll();
+        self.stick = gamepad.stick;
+        let mouse_position = Vec2::from(mouse_position());
+        if self.last_mouse_position != Some(mouse_position) {
+            self.last_mouse_position = Some(mouse_position);
+            self.cursor.position = game.camera.screen_to_world(mouse_position);
+        }
+
+        let aim = self.cursor.position;
+        let map = &self.map;
+        match self.controls {
+            ControlScheme::Closest => {
+                let fired = is_mouse_button_pressed(MouseButton::Left)
+                    || any_key_pressed(&map.fire_closest)
+                    || gamepad.any_pressed(&map.gamepad_fire_closest);
+                if fired {
+                    self.fires.extend(fire_from_closest(&game.world, aim));
+                }
+            }
+            ControlScheme::Keys => {
+                for launcher in 0..map.fire_launcher.len().max(map.gamepad_fire_launcher.len()) {
+                    let keys = map.fire_launcher.get(launcher).map_or(&[][..], |keys| keys);
+                    let buttons = map
+                        .gamepad_fire_launcher
+                        .get(launcher)
+                        .map_or(&[][..], |buttons| buttons);
+                    if any_key_pressed(keys) || gamepad.any_pressed(buttons) {
+                        self.fires.push(Fire { launcher, aim });
+                    }
+                }
+            }
+        }
+
+        self.collect_taps(&game.world, |tap| game.camera.screen_to_world(tap));
+    }
+
+    /// Taps fire from the launcher closest to them whatever the control scheme.
+    pub fn collect_taps(&mut self, world: &World, to_level: impl Fn(Vec2) -> Vec2) {
+        for tap in taps(self.touches.touches()) {
+            let tap = to_level(tap);
+            self.cursor.position = tap;
+            self.fires.extend(fire_from_closest(world, tap));
+        }
+    }
+
+    /// Drops the shots collected so far, for presses that went to the UI.
+    pub fn cancel_fires(&mut self) {
+        self.fires.clear();
+    }
+}
+
+impl InputSource for PlayerInput {
+    fn poll(&mut self, game: &Game) -> TickInput {
+        let mut direction = self.stick;
+        let map = &self.map;
+        for (keys, step) in [
+            (&map.cursor_up, vec2(0.0, -1.0)),
+            (&map.cursor_down, vec2(0.0, 1.0)),
+            (&map.cursor_left, vec2(-1.0, 0.0)),
+            (&map.cursor_right, vec2(1.0, 0.0)),
+        ] {
+            if any_key_down(keys) {
+                direction += step;
+            }
+        }
+        self.cursor.steer(direction, game.level_size);
+
+        TickInput {
+            aim: self.cursor.position,
+            fires: std::mem::take(&mut self.fires),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{AnimationState, LAUNCHER_FIRE};
+    use crate::ecs::{Components, Launcher, Position};
+    use std::collections::VecDeque;
+
+    /// Hands out one frame of touches per call, then none.
+    #[derive(Default)]
+    struct SimulatedTouches {
+        frames: VecDeque<Vec<Touch>>,
+    }
+
+    impl TouchSource for SimulatedTouches {
+        fn touches(&mut self) -> Vec<Touch> {
+            self.frames.pop_front().unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn cursor_speeds_up_while_held_and_stays_in_the_level() {
+        let level_size = vec2(100.0, 100.0);
+        let mut cursor = VirtualCursor {
+            position: vec2(50.0, 50.0),
+            ..Default::default()
+        };
+        let mut steps = vec![];
+        for _ in 0..4 {
+            let before = cursor.position;
+            cursor.steer(vec2(1.0, 0.0), level_size);
+            steps.push(cursor.position.x - before.x);
+        }
+        assert_eq!(steps[0], CURSOR_START_SPEED);
+        assert!(steps.windows(2).all(|pair| pair[1] > pair[0]));
+
+        cursor.steer(Vec2::ZERO, level_size);
+        cursor.steer(vec2(0.0, -1.0), level_size);
+        assert_eq!(cursor.position.y, 50.0 - CURSOR_START_SPEED);
+
+        for _ in 0..100 {
+            cursor.steer(vec2(1.0, 1.0), level_size);
+        }
+        assert_eq!(cursor.position, level_size);
+    }
+
+    #[test]
+    fn every_new_tap_fires_from_the_launcher_closest_to_it() {
+        let mut world = World::new();
+        for x in [0.0, 200.0, 400.0] {
+            world.spawn(Components {
+                position: Some(Position(vec2(x, 500.0))),
+                launcher: Some(Launcher {
+                    target: Vec2::ZERO,
+                    ammo: 10,
+                    max_ammo: 10,
+                    barrel: AnimationState::done(LAUNCHER_FIRE),
+                }),
+                ..Default::default()
+            });
+        }
+        let touch = |id, phase, x| Touch {
+            id,
+            phase,
+            position: vec2(x, 100.0),
+        };
+        let mut touches = SimulatedTouches::default();
+        touches.frames.push_back(vec![
+            touch(2, TouchPhase::Started, 390.0),
+            touch(1, TouchPhase::Started, 10.0),
+            touch(0, TouchPhase::Moved, 200.0),
+        ]);
+        touches
+            .frames
+            .push_back(vec![touch(1, TouchPhase::Ended, 10.0)]);
+
+        // taps fire from the closest launcher even when keys pick launchers
+        let mut input =
+            PlayerInput::with_touches(ControlScheme::Keys, InputMap::default(), Box::new(touches));
+        let launchers = |fires: Vec<Fire>| -> Vec<usize> {
+            fires.into_iter().map(|fire| fire.launcher).collect()
+        };
+        // frames without a tick keep their taps for the next one
+        for _ in 0..3 {
+            input.collect_taps(&world, |tap| tap);
+        }
+        assert_eq!(launchers(std::mem::take(&mut input.fires)), vec![0, 2]);
+        input.collect_taps(&world, |tap| tap);
+        assert!(input.fires.is_empty());
+        assert_eq!(input.cursor.position, vec2(390.0, 100.0));
+    }
+
+    #[test]
+    fn input_map_keeps_defaults_for_missing_bindings() {
+        let map: InputMap = serde_json::from_str(r#"{ "fire_closest": ["Enter", "F"] }"#).unwrap();
+        assert_eq!(map.fire_closest, vec![Key(KeyCode::Enter), Key(KeyCode::F)]);
+        assert_eq!(map.cursor_up, InputMap::default().cursor_up);
+
+        let json = serde_json::to_string(&InputMap::default()).unwrap();
+        assert_eq!(
+            serde_json::from_str::<InputMap>(&json).unwrap(),
+            InputMap::default()
+        );
+
+        let error = serde_json::from_str::<InputMap>(r#"{ "cursor_up": ["Nope"] }"#);
+        assert!(error.unwrap_err().to_string().contains("unknown key: Nope"));
+    }
+}
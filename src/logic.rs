@@ -1,378 +1,768 @@
+use crate::animation::{
+    AnimationState, Animations, CITY_DESTROYED, LAUNCHER_FIRE, PLANE_PROPELLER,
+};
+use crate::collision::{collider_shape, sweep_intersects, swept_bounds, Shape};
 use crate::data::{
-    Assets, Building, Cannon, Crosshair, Game, LevelData, Missile, Plane, MISSILE_SIZE,
-    PLANE_HEIGHT, PLANE_WIDTH,
+    Assets, Entity, Game, GameEvent, HitTarget, LevelData, ScheduledLaunch, Warhead,
+    CANNON_BASE_HEIGHT, CANNON_BASE_WIDTH, CELL_SIZE, DEFAULT_CANNON_AMMO, DEFAULT_PLANE_SPEED,
+    ENEMY_TRAIL_THICKNESS, MIRV_SPREAD, MISSILE_COLLIDER, MISSILE_SIZE, PLANE_HEIGHT, PLANE_WIDTH,
+    PLAYER_MISSILE_SPEED, PLAYER_TRAIL_THICKNESS, TICKS_PER_SECOND, TRAIL_FADE_TICKS,
+    TRAIL_STRAIGHT_COS, WAVE_SPEED_STEP,
+};
+use crate::ecs::{
+    Anchor, City, Collider, Components, Crosshair, EntityId, Facing, Health, Launcher, Layer,
+    Lifetime, Missile, Position, Reward, Sprite, Team, Trail, Velocity, World,
 };
+use crate::grid::SpatialGrid;
+use crate::hud::scaled_reward;
+use crate::input::TickInput;
+use crate::palette::PaletteColor;
+use crate::particles::{DEBRIS, FIREBALL, SMOKE, SMOKE_INTERVAL, SPARKS};
 use macroquad::{
     audio::{self, play_sound_once},
     prelude::*,
 };
+use std::collections::BTreeSet;
 
-pub fn clean_missiles_out_of_window(missiles: &mut Vec<Missile>) {
-    for missile in missiles {
-        if missile.x < 0.0
-            || missile.x > screen_width()
-            || missile.y < 0.0
-            || missile.y > screen_height()
-        {
-            missile.should_destroy = true;
+pub fn movement_system(world: &mut World) {
+    for entity in world.values_mut() {
+        let velocity = entity.velocity();
+        if let Some(position) = &mut entity.position {
+            position.0 += velocity;
+        }
+        if let Some(missile) = &mut entity.missile {
+            missile.trail_length += velocity.length();
         }
     }
 }
 
-pub fn update_missile(missile: &mut Missile) {
-    missile.x += missile.direction.x * missile.speed;
-    missile.y += missile.direction.y * missile.speed;
-    missile.trail_length += missile.speed;
+/// Where a missile was launched from.
+pub fn missile_origin(position: Vec2, velocity: Vec2, missile: &Missile) -> Vec2 {
+    position - velocity.normalize_or_zero() * missile.trail_length
 }
 
-pub fn update_plane(plane: &mut Plane) {
-    plane.x += plane.direction.x * plane.speed;
-    plane.y += plane.direction.y * plane.speed;
+/// Back ends of the missiles in flight, where their smoke comes out.
+pub fn missile_tails(world: &World) -> Vec<Vec2> {
+    world
+        .values()
+        .filter(|entity| entity.missile.is_some())
+        .filter_map(|entity| {
+            let position = entity.position?.0;
+            Some(position - entity.velocity().normalize_or_zero() * MISSILE_SIZE / 2.0)
+        })
+        .collect()
 }
 
-pub fn update_cannon(cannon: &mut Cannon, closest_cannon: Option<Cannon>, cam: &Camera2D) {
-    let mouse_position = mouse_position();
-    let world_position = cam.screen_to_world(vec2(mouse_position.0, mouse_position.1));
-    if let Some(closest_cannon) = closest_cannon {
-        if closest_cannon.x == cannon.x && closest_cannon.y == cannon.y {
-            cannon.target = world_position;
-        }
+pub fn flight_ticks(distance: f32, speed: f32) -> u32 {
+    if speed <= 0.0 {
+        return 0;
     }
+
+    (distance / speed).ceil() as u32
 }
 
-pub fn update_crosshairs(crosshairs: &mut Vec<Crosshair>, missiles: &mut Vec<Missile>) {
-    for crosshair in crosshairs {
-        if crosshair.should_destroy {
+/// Splits MIRVs half way to their target: each carries on as a standard
+/// warhead and two more copies of it spread around the target.
+pub fn warhead_system(world: &mut World) {
+    let mut new_warheads = vec![];
+    for entity in world.values_mut() {
+        let (Some(position), Some(missile)) = (entity.position, entity.missile) else {
+            continue;
+        };
+        let target = match missile.target {
+            Some(target) if missile.warhead == Warhead::Mirv => target,
+            _ => continue,
+        };
+        let velocity = entity.velocity();
+        let origin = missile_origin(position.0, velocity, &missile);
+        if missile.trail_length < origin.distance(target) / 2.0 {
             continue;
         }
 
-        if crosshair.missile_index >= missiles.len()
-            || missiles[crosshair.missile_index].should_destroy
-        {
-            crosshair.should_destroy = true;
-            continue;
+        entity.missile = Some(Missile {
+            warhead: Warhead::Standard,
+            ..missile
+        });
+        for offset in [-MIRV_SPREAD, MIRV_SPREAD] {
+            let target = target + vec2(offset, 0.0);
+            let speed = velocity.length();
+            let mut warhead = entity.clone();
+            warhead.velocity = Some(Velocity((target - position.0).normalize_or_zero() * speed));
+            warhead.lifetime = Some(Lifetime(flight_ticks(position.0.distance(target), speed)));
+            warhead.missile = Some(Missile {
+                trail_length: 0.0,
+                target: Some(target),
+                warhead: Warhead::Standard,
+            });
+            new_warheads.push(warhead);
         }
     }
+
+    for warhead in new_warheads {
+        world.spawn(warhead);
+    }
 }
 
-pub fn fire_missile(
-    missiles: &mut Vec<Missile>,
-    x: f32,
-    y: f32,
-    cannon: &mut Cannon,
-    sfx: &audio::Sound,
-) -> Option<usize> {
-    if cannon.ammo == 0 {
-        return None;
+/// Starts a trail behind every new missile, extends the trails of missiles
+/// in flight and fades out the ones whose missile is gone.
+pub fn trail_system(world: &mut World) {
+    let mut tracked = BTreeSet::new();
+    let mut heads = vec![];
+    for (id, entity) in world.iter() {
+        if let Some(trail) = &entity.trail {
+            tracked.insert(trail.missile);
+            let head = world
+                .get(trail.missile)
+                .filter(|missile| missile.is_alive())
+                .and_then(|missile| missile.position);
+            heads.push((id, head));
+        }
     }
 
-    let direction = cannon.target - vec2(x, y);
-    let direction = direction.normalize();
+    let mut new_trails = vec![];
+    for (id, entity) in world.iter() {
+        if let (Some(position), Some(missile)) = (entity.position, &entity.missile) {
+            if !tracked.contains(&id) {
+                let (color, thickness) = match entity.team {
+                    Some(Team::Enemy) => (PaletteColor::Enemy, ENEMY_TRAIL_THICKNESS),
+                    _ => (PaletteColor::Interceptor, PLAYER_TRAIL_THICKNESS),
+                };
+                new_trails.push(Components {
+                    trail: Some(Trail {
+                        missile: id,
+                        // from where it was launched, it has moved already
+                        points: vec![
+                            missile_origin(position.0, entity.velocity(), missile),
+                            position.0,
+                        ],
+                        color,
+                        thickness,
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+    }
 
-    let missile = Missile::new(x, y, direction, 2.5);
-    play_sound_once(sfx);
-    missiles.push(missile);
-    cannon.ammo -= 1;
+    for (id, head) in heads {
+        let Some(entity) = world.get_mut(id) else {
+            continue;
+        };
+        match (head, &mut entity.trail) {
+            (Some(head), Some(trail)) => {
+                // the last segment grows while the missile flies straight,
+                // a turn starts a new one
+                let straight = match trail.points[..] {
+                    [.., previous, last] => {
+                        let segment = (last - previous).normalize_or_zero();
+                        segment.dot((head.0 - last).normalize_or_zero()) > TRAIL_STRAIGHT_COS
+                    }
+                    _ => false,
+                };
+                match trail.points.last_mut() {
+                    Some(last) if straight => *last = head.0,
+                    _ => trail.points.push(head.0),
+                }
+            }
+            (None, _) if entity.lifetime.is_none() => {
+                entity.lifetime = Some(Lifetime(TRAIL_FADE_TICKS));
+            }
+            _ => {}
+        }
+    }
 
-    Some(missiles.len() - 1)
+    for trail in new_trails {
+        world.spawn(trail);
+    }
 }
 
-pub fn spawn_crosshair(game: &mut Game, missile_index: Option<usize>) {
-    if missile_index.is_none() {
-        return;
+/// Counts lifetimes down and removes what runs out. Missiles that run out
+/// have reached their target and detonate.
+pub fn lifetime_system(world: &mut World, sfx: &audio::Sound, events: &mut Vec<GameEvent>) {
+    let mut expired = vec![];
+    for (id, entity) in world.iter_mut() {
+        if let Some(lifetime) = &mut entity.lifetime {
+            lifetime.0 = lifetime.0.saturating_sub(1);
+            if lifetime.0 == 0 {
+                expired.push(id);
+            }
+        }
     }
 
-    let mouse_position = mouse_position();
-    let world_position = game
-        .camera
-        .screen_to_world(vec2(mouse_position.0, mouse_position.1));
-    let crosshair_position = vec2(world_position.x, world_position.y);
-    let crosshair = Crosshair {
-        x: crosshair_position.x,
-        y: crosshair_position.y,
-        missile_index: missile_index.unwrap(),
-        should_destroy: false,
-    };
-    game.crosshairs.push(crosshair);
+    for id in expired {
+        if let Some(entity) = world.despawn(id) {
+            if let (Some(position), Some(_)) = (entity.position, entity.missile) {
+                events.push(GameEvent::Detonation {
+                    position: position.0,
+                });
+                play_sound_once(sfx);
+            }
+        }
+    }
 }
 
-pub fn get_closeset_cannon_no_ref(cannons: &Vec<Cannon>, cam: &Camera2D) -> Option<Cannon> {
-    let mouse_position = mouse_position();
-    let mouse_position = vec2(mouse_position.0, mouse_position.1);
-    let world_position = cam.screen_to_world(mouse_position);
-    let mut closest_cannon: Option<Cannon> = None;
-    let mut closest_distance = 100000.0;
-    for cannon in cannons {
-        let distance = world_position.distance(vec2(cannon.x, cannon.y));
-        if distance < closest_distance {
-            closest_distance = distance;
-            closest_cannon = Some(cannon.clone());
+pub fn launcher_system(world: &mut World, aim: Vec2) {
+    for launcher in world
+        .values_mut()
+        .filter_map(|entity| entity.launcher.as_mut())
+    {
+        launcher.target = aim;
+    }
+}
+
+/// Crosshairs go away together with the missile they mark the target of.
+pub fn crosshair_system(world: &mut World) {
+    let mut gone = vec![];
+    for (id, entity) in world.iter() {
+        if let Some(crosshair) = entity.crosshair {
+            match world.get(crosshair.missile) {
+                Some(missile) if missile.is_alive() => {}
+                _ => gone.push(id),
+            }
         }
     }
 
-    closest_cannon
+    for id in gone {
+        world.despawn(id);
+    }
 }
 
-pub fn get_closest_cannon_mut<'a>(
-    cannons: &'a mut Vec<Cannon>,
-    cam: &'a Camera2D,
-) -> Option<&'a mut Cannon> {
-    let mouse_position = mouse_position();
-    let mouse_position = vec2(mouse_position.0, mouse_position.1);
-    let world_position = cam.screen_to_world(mouse_position);
-    let mut closest_cannon: Option<&mut Cannon> = None;
-    let mut closest_distance = 100000.0;
-    for cannon in cannons {
-        let distance = world_position.distance(vec2(cannon.x, cannon.y));
-        if distance < closest_distance {
-            closest_distance = distance;
-            closest_cannon = Some(cannon);
+pub fn animation_system(world: &mut World) {
+    for entity in world.values_mut() {
+        if let Some(animation) = &mut entity.animation {
+            animation.advance();
+        }
+        if let Some(launcher) = &mut entity.launcher {
+            launcher.barrel.advance();
         }
     }
-
-    closest_cannon
 }
 
-pub fn aabb_collision(x1: f32, y1: f32, size1: Vec2, x2: f32, y2: f32, size2: Vec2) -> bool {
-    if x1 - size1.x / 2.0 < x2 + size2.y / 2.0
-        && x1 + size1.x / 2.0 > x2 - size2.y / 2.0
-        && y1 - size1.x / 2.0 < y2 + size2.y / 2.0
-        && y1 + size1.x / 2.0 > y2 - size2.y / 2.0
-    {
-        return true;
+/// Leaves a ruin behind every destroyed city that crumbles for as long as
+/// its animation lasts. Ruins only look the part, nothing can hit them.
+pub fn ruin_system(world: &mut World, animations: &Animations) {
+    let ruins: Vec<Components> = world
+        .values()
+        .filter(|entity| entity.city.is_some() && !entity.is_alive())
+        .map(|city| Components {
+            position: city.position,
+            sprite: city.sprite.clone(),
+            animation: Some(AnimationState::new(CITY_DESTROYED)),
+            lifetime: Some(Lifetime(animations.duration(CITY_DESTROYED))),
+            ..Default::default()
+        })
+        .collect();
+    for ruin in ruins {
+        world.spawn(ruin);
     }
+}
 
-    false
+/// Removes destroyed entities and missiles that left the level. Going by the
+/// level rather than the window keeps runs independent of the window size.
+pub fn cleanup_system(world: &mut World, level_size: Vec2) {
+    let level = Rect::new(0.0, 0.0, level_size.x, level_size.y);
+    world.retain(|entity| {
+        let out_of_level = entity.missile.is_some()
+            && entity
+                .position
+                .is_some_and(|position| !level.contains(position.0));
+        entity.is_alive() && !out_of_level
+    });
 }
 
-pub fn missile_hit_building(missile: &Missile, building: &Building) -> bool {
-    return aabb_collision(
-        missile.x,
-        missile.y,
-        Vec2::new(MISSILE_SIZE, MISSILE_SIZE / 2.0),
-        building.x + building.size.x / 2.0,
-        building.y + building.size.y / 2.0,
-        building.size,
-    );
+pub fn fire_missile(game: &mut Game, launcher: EntityId, aim: Vec2) -> Option<EntityId> {
+    let entity = game.world.get_mut(launcher)?;
+    let (position, launcher) = (entity.position?, entity.launcher.as_mut()?);
+    launcher.target = aim;
+    if launcher.ammo == 0 {
+        return None;
+    }
+    launcher.ammo -= 1;
+    launcher.barrel = AnimationState::new(LAUNCHER_FIRE);
+
+    let origin = position.0 + vec2(CANNON_BASE_WIDTH / 2.0, 0.0);
+    let direction = (launcher.target - origin).normalize();
+    play_sound_once(&game.missile_fire_sound);
+    Some(game.world.spawn(Components {
+        team: Some(Team::Player),
+        ..missile_components(
+            origin,
+            direction * PLAYER_MISSILE_SPEED,
+            PaletteColor::Plain,
+        )
+    }))
 }
 
-pub fn missile_hit_plane(missile: &Missile, plane: &Plane) -> bool {
-    return aabb_collision(
-        missile.x,
-        missile.y,
-        Vec2::new(MISSILE_SIZE, MISSILE_SIZE / 2.0),
-        plane.x,
-        plane.y,
-        plane.size,
-    );
+pub fn spawn_crosshair(game: &mut Game, missile: Option<EntityId>, aim: Vec2) {
+    let missile = match missile {
+        Some(missile) => missile,
+        None => return,
+    };
+
+    game.world.spawn(Components {
+        position: Some(Position(aim)),
+        crosshair: Some(Crosshair { missile }),
+        ..Default::default()
+    });
 }
 
-pub fn missile_hit_missile(missile1: &Missile, missile2: &Missile) -> bool {
-    return aabb_collision(
-        missile1.x,
-        missile1.y,
-        Vec2::new(MISSILE_SIZE, MISSILE_SIZE / 2.0),
-        missile2.x,
-        missile2.y,
-        Vec2::new(MISSILE_SIZE, MISSILE_SIZE / 2.0),
-    );
+/// Every launcher in the level, left to right like the original's silos.
+/// Launchers are never removed, so a launcher's place in this list
+/// identifies it across a run.
+pub fn launcher_ids(world: &World) -> Vec<EntityId> {
+    let mut launchers: Vec<(EntityId, f32)> = world
+        .iter()
+        .filter(|(_, entity)| entity.launcher.is_some())
+        .map(|(id, entity)| (id, entity.position.map_or(0.0, |position| position.0.x)))
+        .collect();
+    launchers.sort_by(|a, b| a.1.total_cmp(&b.1));
+    launchers.into_iter().map(|(id, _)| id).collect()
 }
 
-pub fn handle_missile_building_collision(
-    buildings: &mut Vec<Building>,
-    missile: &mut Missile,
-    sfx: &audio::Sound,
-) {
-    for building in buildings {
-        if missile_hit_building(missile, building) {
-            building.should_destroy = true;
-            missile.should_destroy = true;
-            play_sound_once(sfx);
+pub fn get_closest_launcher(world: &World, aim: Vec2) -> Option<EntityId> {
+    let mut closest_launcher = None;
+    let mut closest_distance = 100000.0;
+    for (id, entity) in world.iter() {
+        let (Some(position), Some(_)) = (entity.position, entity.launcher) else {
+            continue;
+        };
+        let distance = aim.distance(position.0);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest_launcher = Some(id);
         }
     }
+
+    closest_launcher
 }
 
-pub fn handle_missile_plane_collision(
-    planes: &mut Vec<Plane>,
-    missile: &mut Missile,
-    score: &mut i32,
-    sfx: &audio::Sound,
-) {
-    for plane in planes {
-        if missile_hit_plane(missile, plane) {
-            plane.should_destroy = true;
-            missile.should_destroy = true;
-            play_sound_once(sfx);
-            *score -= 10;
-        }
-    }
+pub fn entity_shape(entity: &Components) -> Option<Shape> {
+    Some(collider_shape(
+        entity.position?.0,
+        entity.velocity(),
+        entity.collider.as_ref()?,
+    ))
 }
 
-pub fn handle_missile_missile_collision(
-    enemy_missiles: &mut Vec<Missile>,
-    missile: &mut Missile,
-    score: &mut i32,
-    sfx: &audio::Sound,
-) {
-    for enemy_missile in enemy_missiles {
-        if missile_hit_missile(missile, enemy_missile) {
-            enemy_missile.should_destroy = true;
-            missile.should_destroy = true;
-            play_sound_once(sfx);
-            *score += 1;
-        }
+/// The hit test runs after everything has moved and sweeps the attacker over
+/// the step it just made, relative to the target, so speed cannot make it
+/// tunnel through anything.
+pub fn entities_hit(attacker: &Components, target: &Components) -> bool {
+    match (entity_shape(attacker), entity_shape(target)) {
+        (Some(attacker_shape), Some(target_shape)) => sweep_intersects(
+            &attacker_shape,
+            attacker.velocity() - target.velocity(),
+            &target_shape,
+        ),
+        _ => false,
     }
 }
 
-pub fn handle_collisions(game: &mut Game) {
-    for missile in &mut game.enemy_missiles {
-        handle_missile_building_collision(&mut game.buildings, missile, &game.explosion_sound);
-    }
+pub fn entity_bounds(entity: &Components) -> Option<Rect> {
+    entity_shape(entity).map(|shape| swept_bounds(&shape, entity.velocity()))
+}
 
-    for missile in &mut game.player_missiles {
-        handle_missile_plane_collision(
-            &mut game.planes,
-            missile,
-            &mut game.score,
-            &game.explosion_sound,
-        );
-        handle_missile_missile_collision(
-            &mut game.enemy_missiles,
-            missile,
-            &mut game.score,
-            &game.explosion_sound,
-        );
-    }
+/// Whether `attacker` can destroy `target` on contact. Missiles are the
+/// attackers, and two missiles are only matched up from the player's side so
+/// that every pair is handled once.
+pub fn can_hit(attacker: &Components, target: &Components) -> bool {
+    let (Some(attacker_team), Some(target_team)) = (attacker.team, target.team) else {
+        return false;
+    };
+
+    attacker.missile.is_some()
+        && attacker_team.hits(target_team)
+        && (target.missile.is_none() || attacker_team == Team::Player)
 }
 
-pub fn update_game(game: &mut Game) {
-    if is_mouse_button_pressed(MouseButton::Left) {
-        let closest_cannon = get_closest_cannon_mut(&mut game.cannons, &game.camera);
-        if let Some(cannon) = closest_cannon {
-            if cannon.ammo == 0 {
-                return;
-            }
-            let missile_index = fire_missile(
-                &mut game.player_missiles,
-                cannon.x + 16.0,
-                cannon.y,
-                cannon,
-                &game.missile_fire_sound,
-            );
-            spawn_crosshair(game, missile_index);
+/// Every attacker narrows its targets down with a grid rebuilt from this
+/// step's positions before running the exact hit test. Returns the
+/// `(attacker, target)` pairs that met, attacker by attacker.
+pub fn find_hits(world: &World) -> Vec<(EntityId, EntityId)> {
+    let mut grid = SpatialGrid::new();
+    let mut attackers = vec![];
+    for (id, entity) in world.iter() {
+        let Some(bounds) = entity_bounds(entity) else {
+            continue;
+        };
+        grid.insert(id, bounds);
+        if entity.missile.is_some() {
+            attackers.push((id, bounds));
         }
     }
 
-    for missile in &mut game.enemy_missiles {
-        update_missile(missile);
+    let mut hits = vec![];
+    for (attacker_id, bounds) in attackers {
+        for target_id in grid.query(bounds) {
+            let (Some(attacker), Some(target)) = (world.get(attacker_id), world.get(target_id))
+            else {
+                continue;
+            };
+            if attacker_id != target_id
+                && can_hit(attacker, target)
+                && entities_hit(attacker, target)
+            {
+                hits.push((attacker_id, target_id));
+            }
+        }
     }
 
-    for missile in &mut game.player_missiles {
-        update_missile(missile);
+    hits
+}
+
+/// Scores and damages every hit of this step. Something destroyed by an
+/// earlier hit of the step does not hit anything else.
+pub fn collision_system(game: &mut Game) {
+    for (attacker_id, target_id) in find_hits(&game.world) {
+        let (Some(attacker), Some(target)) =
+            (game.world.get(attacker_id), game.world.get(target_id))
+        else {
+            continue;
+        };
+        if !attacker.is_alive() || !target.is_alive() {
+            continue;
+        }
+
+        if attacker.team == Some(Team::Player) {
+            let reward = target.reward.map_or(0, |reward| reward.0);
+            game.score += scaled_reward(reward, game.wave);
+        }
+        let target_kind = if target.missile.is_some() {
+            HitTarget::Missile
+        } else if target.city.is_some() {
+            HitTarget::City
+        } else {
+            HitTarget::Other
+        };
+        let bounds = entity_bounds(attacker).unwrap_or_default();
+        game.events.push(GameEvent::Hit {
+            position: attacker
+                .position
+                .map_or(bounds.center(), |position| position.0),
+            target: target_kind,
+            target_bounds: entity_bounds(target).unwrap_or(bounds),
+        });
+        for id in [attacker_id, target_id] {
+            if let Some(health) = game
+                .world
+                .get_mut(id)
+                .and_then(|entity| entity.health.as_mut())
+            {
+                health.0 = health.0.saturating_sub(1);
+            }
+        }
+        play_sound_once(&game.explosion_sound);
     }
+}
 
-    for plane in &mut game.planes {
-        update_plane(plane);
+pub fn particle_system(game: &mut Game) {
+    for event in &game.events {
+        match *event {
+            GameEvent::Hit {
+                position,
+                target,
+                target_bounds,
+            } => {
+                game.particles.emit(&FIREBALL, position, 3);
+                game.particles.emit(&SPARKS, position, 1);
+                if target == HitTarget::City {
+                    let top = vec2(target_bounds.center().x, target_bounds.y);
+                    game.particles.emit(&DEBRIS, top, 8);
+                }
+            }
+            GameEvent::Detonation { position } => {
+                game.particles.emit(&FIREBALL, position, 3);
+            }
+        }
     }
 
-    let closest_cannon = get_closeset_cannon_no_ref(&game.cannons, &game.camera);
+    if game.tick.is_multiple_of(SMOKE_INTERVAL) {
+        for tail in missile_tails(&game.world) {
+            game.particles.emit(&SMOKE, tail, 1);
+        }
+    }
+    game.particles.update();
+}
 
-    for cannon in &mut game.cannons {
-        update_cannon(cannon, closest_cannon, &game.camera);
+/// Runs one tick. Everything the player does comes in through `input`, which
+/// together with the seed makes a run reproducible.
+pub fn update_game(game: &mut Game, input: &TickInput) {
+    game.aim = input.aim;
+    game.events.clear();
+    launcher_system(&mut game.world, input.aim);
+    let launchers = launcher_ids(&game.world);
+    for fire in &input.fires {
+        if let Some(&launcher) = launchers.get(fire.launcher) {
+            let missile = fire_missile(game, launcher, fire.aim);
+            spawn_crosshair(game, missile, fire.aim);
+        }
     }
 
-    handle_collisions(game);
-    update_crosshairs(&mut game.crosshairs, &mut game.player_missiles);
-    cleanup(game);
+    update_waves(game);
+    movement_system(&mut game.world);
+    warhead_system(&mut game.world);
+    trail_system(&mut game.world);
+    lifetime_system(&mut game.world, &game.explosion_sound, &mut game.events);
+    collision_system(game);
+    crosshair_system(&mut game.world);
+    animation_system(&mut game.world);
+    ruin_system(&mut game.world, &game.animations);
+    cleanup_system(&mut game.world, game.level_size);
+    particle_system(game);
+    game.tick += 1;
 
-    if game.buildings.len() == 0 {
+    if !game.world.values().any(|entity| entity.city.is_some()) {
         game.game_over = true;
     }
 }
 
-pub fn cleanup_buildings(game: &mut Game) {
-    game.buildings.retain(|building| !building.should_destroy);
-}
-
-pub fn cleanup_planes(game: &mut Game) {
-    game.planes.retain(|plane| !plane.should_destroy);
-}
-
-pub fn cleanup_enemy_missiles(game: &mut Game) {
-    game.enemy_missiles
-        .retain(|missile| !missile.should_destroy);
+/// A missile flying from its centre at `position`. The team and, for enemy
+/// warheads, the target are added by the caller.
+pub fn missile_components(position: Vec2, velocity: Vec2, color: PaletteColor) -> Components {
+    Components {
+        position: Some(Position(position)),
+        velocity: Some(Velocity(velocity)),
+        collider: Some(MISSILE_COLLIDER),
+        sprite: Some(Sprite {
+            name: "missile".to_string(),
+            size: vec2(MISSILE_SIZE, MISSILE_SIZE / 2.0),
+            color,
+            layer: Layer::Missiles,
+            anchor: Anchor::Center,
+            facing: Facing::Heading,
+        }),
+        health: Some(Health(1)),
+        missile: Some(Missile {
+            trail_length: 0.0,
+            target: None,
+            warhead: Warhead::Standard,
+        }),
+        ..Default::default()
+    }
 }
 
-pub fn cleanup_player_missiles(game: &mut Game) {
-    game.player_missiles
-        .retain(|missile| !missile.should_destroy);
-}
+/// An enemy warhead. Aimed ones detonate once they reach their target.
+pub fn spawn_enemy_missile_at(
+    game: &mut Game,
+    position: Vec2,
+    velocity: Vec2,
+    target: Option<Vec2>,
+    warhead: Warhead,
+) -> EntityId {
+    let mut components = missile_components(position, velocity, PaletteColor::Enemy);
+    components.team = Some(Team::Enemy);
+    components.reward = Some(Reward(1));
+    components.missile = Some(Missile {
+        trail_length: 0.0,
+        target,
+        warhead,
+    });
+    if let Some(target) = target {
+        components.lifetime = Some(Lifetime(flight_ticks(
+            position.distance(target),
+            velocity.length(),
+        )));
+    }
 
-pub fn cleanup_crosshairs(game: &mut Game) {
-    game.crosshairs
-        .retain(|crosshair| !crosshair.should_destroy);
+    play_sound_once(&game.enemy_missile_sound);
+    game.world.spawn(components)
 }
 
-pub fn cleanup(game: &mut Game) {
-    cleanup_buildings(game);
-    cleanup_planes(game);
-    cleanup_crosshairs(game);
-    cleanup_enemy_missiles(game);
-    cleanup_player_missiles(game);
-    clean_missiles_out_of_window(&mut game.enemy_missiles);
-    clean_missiles_out_of_window(&mut game.player_missiles);
+/// Enemy missiles leave from the centre of their spawnpoint's cell, the same
+/// point the editor draws launches from.
+fn spawnpoint_origin(spawnpoint: &Entity) -> Vec2 {
+    vec2(
+        spawnpoint.x + CELL_SIZE / 2.0,
+        spawnpoint.y + CELL_SIZE / 2.0,
+    )
 }
 
 pub fn spawn_enemy_missile(game: &mut Game) {
-    let spawnpoint_index = rand::gen_range(0, game.enemy_missiles_spawnpoints.len());
+    let spawnpoint_index = game
+        .rng
+        .gen_range_usize(0, game.enemy_missiles_spawnpoints.len());
     if spawnpoint_index >= game.enemy_missiles_spawnpoints.len() {
         return;
     }
-    let spawnpoint = &game.enemy_missiles_spawnpoints[spawnpoint_index];
-    let x = spawnpoint.x;
-    let y = spawnpoint.y;
-    let direction = vec2(rand::gen_range(-1.0, 1.0), rand::gen_range(0.2, 1.0));
+    let position = spawnpoint_origin(&game.enemy_missiles_spawnpoints[spawnpoint_index]);
+    let direction = vec2(
+        game.rng.gen_range_f32(-1.0, 1.0),
+        game.rng.gen_range_f32(0.2, 1.0),
+    );
     let direction = direction.normalize();
-    let missile = Missile::new(x, y, direction, 1.0);
-    play_sound_once(&game.enemy_missile_sound);
-    game.enemy_missiles.push(missile);
+    spawn_enemy_missile_at(game, position, direction, None, Warhead::Standard);
 }
 
-pub fn spawn_cannon(game: &mut Game, x: f32, y: f32) {
-    let cannon = Cannon {
-        x,
-        y,
-        target: vec2(0.0, 0.0),
-        ammo: 10,
-    };
-
-    game.cannons.push(cannon);
+pub fn spawn_cannon(game: &mut Game, x: f32, y: f32, ammo: u32) {
+    game.world.spawn(Components {
+        position: Some(Position(vec2(x, y))),
+        sprite: Some(Sprite {
+            name: "missile_launcher_part_1".to_string(),
+            size: vec2(CANNON_BASE_WIDTH, CANNON_BASE_HEIGHT),
+            color: PaletteColor::Plain,
+            layer: Layer::Launchers,
+            anchor: Anchor::TopLeft,
+            facing: Facing::Fixed,
+        }),
+        team: Some(Team::Player),
+        launcher: Some(Launcher {
+            target: vec2(0.0, 0.0),
+            ammo,
+            max_ammo: ammo,
+            barrel: AnimationState::done(LAUNCHER_FIRE),
+        }),
+        ..Default::default()
+    });
 }
 
 pub fn spawn_building(game: &mut Game, x: f32, y: f32, id: u8) {
-    let building = Building {
-        x,
-        y,
-        size: vec2(64.0, 64.0),
-        texture: game.building_textures[id as usize].clone(),
-        should_destroy: false,
-    };
+    let size = vec2(64.0, 64.0);
+    game.world.spawn(Components {
+        position: Some(Position(vec2(x, y))),
+        collider: Some(Collider::Rect { size }),
+        sprite: Some(Sprite {
+            name: format!("building_{}", id + 1),
+            size,
+            color: PaletteColor::City,
+            layer: Layer::Buildings,
+            anchor: Anchor::TopLeft,
+            facing: Facing::Fixed,
+        }),
+        health: Some(Health(1)),
+        team: Some(Team::Player),
+        city: Some(City),
+        ..Default::default()
+    });
+}
 
-    game.buildings.push(building);
+pub fn spawn_plane(game: &mut Game, x: f32, y: f32, speed: f32) {
+    let size = vec2(PLANE_WIDTH, PLANE_HEIGHT);
+    game.world.spawn(Components {
+        position: Some(Position(vec2(x, y))),
+        velocity: Some(Velocity(get_plane_direction(x, game.level_size.x) * speed)),
+        collider: Some(Collider::Rect { size }),
+        sprite: Some(Sprite {
+            name: "plane".to_string(),
+            size,
+            color: PaletteColor::Plain,
+            layer: Layer::Planes,
+            anchor: Anchor::TopLeft,
+            facing: Facing::FlipX,
+        }),
+        animation: Some(AnimationState::new(PLANE_PROPELLER)),
+        health: Some(Health(1)),
+        team: Some(Team::Neutral),
+        reward: Some(Reward(-10)),
+        ..Default::default()
+    });
 }
 
 pub fn spawn_enemy_missiles(game: &mut Game) {
-    let num_missiles = rand::gen_range(10, 15);
+    let num_missiles = game.rng.gen_range_usize(10, 15);
     for _ in 0..num_missiles {
         spawn_enemy_missile(game);
     }
 }
 
-pub fn handle_resize(last_screen_width: f32, last_screen_height: f32, game: &mut Game) {
+pub fn spawn_launch(game: &mut Game, scheduled: &ScheduledLaunch) {
+    let launch = &scheduled.launch;
+    let speed = launch.speed * (1.0 + WAVE_SPEED_STEP * (game.wave - 1) as f32);
+    let target = vec2(launch.target_x, launch.target_y);
+    let velocity = (target - scheduled.origin).normalize_or_zero() * speed;
+    spawn_enemy_missile_at(
+        game,
+        scheduled.origin,
+        velocity,
+        Some(target),
+        launch.warhead,
+    );
+}
+
+/// Every authored launch of the level, latest first so that due launches can
+/// be popped off the end.
+fn scheduled_launches(spawnpoints: &[Entity]) -> Vec<ScheduledLaunch> {
+    let mut queue = vec![];
+    for spawnpoint in spawnpoints {
+        let origin = spawnpoint_origin(spawnpoint);
+        for launch in &spawnpoint.launches {
+            queue.push(ScheduledLaunch {
+                tick: (launch.time * TICKS_PER_SECOND).round() as u32,
+                origin,
+                launch: launch.clone(),
+            });
+        }
+    }
+    queue.sort_by_key(|scheduled| std::cmp::Reverse(scheduled.tick));
+    queue
+}
+
+/// Takes the launches due by `tick` off the queue, in the order they fire.
+fn due_launches(queue: &mut Vec<ScheduledLaunch>, tick: u32) -> Vec<ScheduledLaunch> {
+    let due = queue.partition_point(|scheduled| scheduled.tick > tick);
+    let mut due = queue.split_off(due);
+    due.reverse();
+    due
+}
+
+/// A wave is over once its schedule has played out and no enemy missile is
+/// left, so the next wave never cuts into a scheduled one between launches.
+fn wave_is_over(world: &World, launch_queue: &[ScheduledLaunch]) -> bool {
+    launch_queue.is_empty()
+        && !world
+            .values()
+            .any(|entity| entity.missile.is_some() && entity.team == Some(Team::Enemy))
+}
+
+/// Starts the next wave: launchers are restocked and enemy missiles either
+/// follow the level's authored launches or, for levels without any, are
+/// fired all at once from random spawnpoints.
+pub fn start_wave(game: &mut Game) {
+    game.wave += 1;
+    game.wave_tick = 0;
+    for launcher in game
+        .world
+        .values_mut()
+        .filter_map(|entity| entity.launcher.as_mut())
+    {
+        launcher.ammo = launcher.max_ammo;
+    }
+
+    game.launch_queue = scheduled_launches(&game.enemy_missiles_spawnpoints);
+    if game.launch_queue.is_empty() {
+        spawn_enemy_missiles(game);
+    }
+}
+
+pub fn update_waves(game: &mut Game) {
+    for scheduled in due_launches(&mut game.launch_queue, game.wave_tick) {
+        spawn_launch(game, &scheduled);
+    }
+    game.wave_tick += 1;
+
+    if wave_is_over(&game.world, &game.launch_queue) && !game.enemy_missiles_spawnpoints.is_empty()
+    {
+        start_wave(game);
+    }
+}
+
+/// Scales the level canvas to fit the window while keeping its aspect ratio.
+/// The leftover space is left black by `Canvas::present`.
+pub fn fit_camera_to_level(camera: &mut Camera2D, level_size: Vec2) {
     let screen_width = screen_width();
     let screen_height = screen_height();
-    if screen_width != last_screen_width || screen_height != last_screen_height {
-        game.camera.zoom = vec2((1.0 / screen_width) * 2.0, (1.0 / screen_height) * 2.0);
+    let scale = (screen_width / level_size.x).min(screen_height / level_size.y);
+    camera.target = level_size / 2.0;
+    camera.zoom = vec2(
+        (1.0 / screen_width) * 2.0 * scale,
+        (1.0 / screen_height) * 2.0 * scale,
+    );
+}
+
+pub fn handle_resize(game: &mut Game) {
+    let screen_size = vec2(screen_width(), screen_height());
+    if screen_size != game.screen_size {
+        game.screen_size = screen_size;
+        fit_camera_to_level(&mut game.camera, game.level_size);
     }
 }
 
@@ -382,27 +772,30 @@ pub fn load_level_from_file(path: &str) -> LevelData {
     level_data
 }
 
-pub fn load_level(game: &mut Game) {
-    let level_data = load_level_from_file("level.json");
+pub fn load_level(game: &mut Game, level_data: LevelData) {
+    game.level_size = vec2(level_data.width, level_data.height);
+    fit_camera_to_level(&mut game.camera, game.level_size);
+
     for building in level_data.buildings {
         spawn_building(game, building.x, building.y, building.id);
     }
 
     for cannon in level_data.cannons {
-        spawn_cannon(game, cannon.x, cannon.y);
+        spawn_cannon(
+            game,
+            cannon.x,
+            cannon.y,
+            cannon.ammo.unwrap_or(DEFAULT_CANNON_AMMO),
+        );
     }
 
     for plane in level_data.planes {
-        let plane = Plane {
-            x: plane.x,
-            y: plane.y,
-            direction: get_plane_direction(plane.x),
-            speed: 1.0,
-            size: vec2(PLANE_WIDTH, PLANE_HEIGHT),
-            should_destroy: false,
-        };
-
-        game.planes.push(plane);
+        spawn_plane(
+            game,
+            plane.x,
+            plane.y,
+            plane.speed.unwrap_or(DEFAULT_PLANE_SPEED),
+        );
     }
 
     for enemy_missile_spawnpoint in level_data.enemy_missiles {
@@ -415,10 +808,269 @@ pub fn load_level(game: &mut Game) {
     }
 }
 
-pub fn get_plane_direction(x: f32) -> Vec2 {
-    if x < screen_width() * 0.5 {
+pub fn get_plane_direction(x: f32, level_width: f32) -> Vec2 {
+    if x < level_width * 0.5 {
         return vec2(1.0, 0.0);
     }
 
-    return vec2(-1.0, 0.0);
+    vec2(-1.0, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::load_animations_from_file;
+    use crate::data::Launch;
+
+    fn missile(position: Vec2, velocity: Vec2, team: Team) -> Components {
+        Components {
+            team: Some(team),
+            sprite: None,
+            ..missile_components(position, velocity, PaletteColor::Plain)
+        }
+    }
+
+    fn building(position: Vec2) -> Components {
+        Components {
+            position: Some(Position(position)),
+            collider: Some(Collider::Rect {
+                size: vec2(64.0, 64.0),
+            }),
+            health: Some(Health(1)),
+            team: Some(Team::Player),
+            city: Some(City),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn movement_moves_every_kind_and_grows_trails() {
+        let mut world = World::new();
+        let missile = world.spawn(missile(vec2(0.0, 0.0), vec2(3.0, 4.0), Team::Enemy));
+        let plane = world.spawn(Components {
+            position: Some(Position(vec2(10.0, 10.0))),
+            velocity: Some(Velocity(vec2(-1.0, 0.0))),
+            ..Default::default()
+        });
+        let city = world.spawn(building(vec2(100.0, 100.0)));
+
+        movement_system(&mut world);
+        let missile = world.get(missile).unwrap();
+        assert_eq!(missile.position, Some(Position(vec2(3.0, 4.0))));
+        assert_eq!(missile.missile.unwrap().trail_length, 5.0);
+        assert_eq!(
+            world.get(plane).unwrap().position,
+            Some(Position(vec2(9.0, 10.0)))
+        );
+        assert_eq!(
+            world.get(city).unwrap().position,
+            Some(Position(vec2(100.0, 100.0)))
+        );
+    }
+
+    #[test]
+    fn mirv_splits_half_way_into_three_warheads() {
+        let mut world = World::new();
+        let mut mirv = missile(vec2(100.0, 0.0), vec2(0.0, 10.0), Team::Enemy);
+        mirv.missile = Some(Missile {
+            trail_length: 0.0,
+            target: Some(vec2(100.0, 100.0)),
+            warhead: Warhead::Mirv,
+        });
+        world.spawn(mirv);
+
+        for _ in 0..4 {
+            movement_system(&mut world);
+            warhead_system(&mut world);
+        }
+        assert_eq!(world.values().count(), 1);
+
+        movement_system(&mut world);
+        warhead_system(&mut world);
+        let mut targets: Vec<f32> = world
+            .values()
+            .map(|entity| entity.missile.unwrap().target.unwrap().x)
+            .collect();
+        targets.sort_by(f32::total_cmp);
+        assert_eq!(
+            targets,
+            vec![100.0 - MIRV_SPREAD, 100.0, 100.0 + MIRV_SPREAD]
+        );
+        assert!(world
+            .values()
+            .all(|entity| entity.missile.unwrap().warhead == Warhead::Standard));
+    }
+
+    #[test]
+    fn trails_follow_turns_and_fade_once_the_missile_is_gone() {
+        let mut world = World::new();
+        let id = world.spawn(missile(vec2(0.0, 0.0), vec2(2.0, 0.0), Team::Enemy));
+        let step = |world: &mut World| {
+            movement_system(world);
+            trail_system(world);
+        };
+        for _ in 0..10 {
+            step(&mut world);
+        }
+        world.get_mut(id).unwrap().velocity = Some(Velocity(vec2(0.0, 2.0)));
+        for _ in 0..10 {
+            step(&mut world);
+        }
+
+        let trail = |world: &World| {
+            world
+                .values()
+                .find_map(|entity| entity.trail.clone())
+                .unwrap()
+        };
+        let points = trail(&world).points;
+        assert_eq!(points.first(), Some(&vec2(0.0, 0.0)));
+        assert_eq!(points.last(), Some(&vec2(20.0, 20.0)));
+        assert!(points.contains(&vec2(20.0, 0.0)), "the turn is kept");
+        assert_eq!(trail(&world).color, PaletteColor::Enemy);
+
+        world.despawn(id);
+        trail_system(&mut world);
+        let fading = world.values().find(|entity| entity.trail.is_some());
+        assert_eq!(
+            fading.and_then(|entity| entity.lifetime),
+            Some(Lifetime(TRAIL_FADE_TICKS))
+        );
+        assert_eq!(trail(&world).points, points);
+    }
+
+    #[test]
+    fn teams_decide_who_hits_whom() {
+        let player = missile(Vec2::ZERO, vec2(0.0, -1.0), Team::Player);
+        let enemy = missile(Vec2::ZERO, vec2(0.0, 1.0), Team::Enemy);
+        let city = building(Vec2::ZERO);
+        let plane = Components {
+            team: Some(Team::Neutral),
+            ..building(Vec2::ZERO)
+        };
+
+        assert!(can_hit(&player, &enemy));
+        assert!(can_hit(&player, &plane));
+        assert!(can_hit(&enemy, &city));
+        // missile pairs are only matched from the player's side
+        assert!(!can_hit(&enemy, &player));
+        assert!(!can_hit(&player, &city));
+        assert!(!can_hit(&enemy, &plane));
+        assert!(!can_hit(&city, &enemy));
+    }
+
+    #[test]
+    fn smoke_comes_out_of_the_back_of_missiles() {
+        let mut world = World::new();
+        let mut long_trail = missile(vec2(100.0, 400.0), vec2(0.0, 2.0), Team::Enemy);
+        long_trail.missile.as_mut().unwrap().trail_length = 400.0;
+        world.spawn(long_trail);
+
+        let tails = missile_tails(&world);
+        assert_eq!(tails, vec![vec2(100.0, 400.0 - MISSILE_SIZE / 2.0)]);
+        assert!(tails[0].distance(vec2(100.0, 400.0)) <= MISSILE_SIZE);
+    }
+
+    #[test]
+    fn planes_and_cleanup_go_by_the_level_not_the_window() {
+        let level_size = vec2(1920.0, 1080.0);
+        assert_eq!(get_plane_direction(900.0, level_size.x), vec2(1.0, 0.0));
+        assert_eq!(get_plane_direction(1000.0, level_size.x), vec2(-1.0, 0.0));
+
+        let mut world = World::new();
+        let inside = world.spawn(missile(vec2(1500.0, 900.0), Vec2::ZERO, Team::Enemy));
+        let outside = world.spawn(missile(vec2(1500.0, 1200.0), Vec2::ZERO, Team::Enemy));
+        cleanup_system(&mut world, level_size);
+        assert!(world.get(inside).is_some());
+        assert!(world.get(outside).is_none());
+    }
+
+    #[test]
+    fn launchers_are_numbered_left_to_right_whatever_the_spawn_order() {
+        let mut world = World::new();
+        let ids: Vec<EntityId> = [416.0, 640.0, 192.0]
+            .into_iter()
+            .map(|x| {
+                world.spawn(Components {
+                    position: Some(Position(vec2(x, 550.0))),
+                    launcher: Some(Launcher {
+                        target: Vec2::ZERO,
+                        ammo: 10,
+                        max_ammo: 10,
+                        barrel: AnimationState::done(LAUNCHER_FIRE),
+                    }),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        world.spawn(building(vec2(0.0, 500.0)));
+
+        assert_eq!(launcher_ids(&world), vec![ids[2], ids[0], ids[1]]);
+    }
+
+    #[test]
+    fn scheduled_waves_play_out_and_levels_without_launches_go_random() {
+        let launch = |time| Launch {
+            time,
+            target_x: 400.0,
+            target_y: 600.0,
+            speed: 2.0,
+            warhead: Warhead::Standard,
+        };
+        let spawnpoint = |x, launches| Entity {
+            x,
+            y: 0.0,
+            id: 0,
+            ammo: None,
+            speed: None,
+            tile: None,
+            launches,
+        };
+        let mut spawnpoints = vec![
+            spawnpoint(0.0, vec![launch(1.0), launch(0.0)]),
+            spawnpoint(64.0, vec![]),
+        ];
+        let mut world = World::new();
+
+        let mut queue = scheduled_launches(&spawnpoints);
+        let first = due_launches(&mut queue, 0);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].origin, vec2(CELL_SIZE / 2.0, CELL_SIZE / 2.0));
+        // nothing is in the air between launches, the wave still has to wait
+        assert!(!wave_is_over(&world, &queue));
+
+        let tick = TICKS_PER_SECOND as u32;
+        assert!(due_launches(&mut queue, tick - 1).is_empty());
+        assert_eq!(due_launches(&mut queue, tick).len(), 1);
+        let last = world.spawn(missile(Vec2::ZERO, Vec2::ONE, Team::Enemy));
+        assert!(!wave_is_over(&world, &queue));
+        world.despawn(last);
+        assert!(wave_is_over(&world, &queue));
+
+        // the next wave follows the schedule again, only a level without any
+        // launches falls back to random bursts
+        assert_eq!(scheduled_launches(&spawnpoints).len(), 2);
+        spawnpoints[0].launches.clear();
+        assert!(scheduled_launches(&spawnpoints).is_empty());
+    }
+
+    #[test]
+    fn destroyed_cities_crumble_as_ruins_nothing_can_hit() {
+        let animations = load_animations_from_file("animations.json");
+        let mut world = World::new();
+        let city = world.spawn(building(vec2(100.0, 500.0)));
+        world.get_mut(city).unwrap().health = Some(Health(0));
+
+        ruin_system(&mut world, &animations);
+        cleanup_system(&mut world, vec2(800.0, 600.0));
+        assert!(world.get(city).is_none());
+        let ruin = world.values().next().unwrap().clone();
+        assert_eq!(ruin.position, Some(Position(vec2(100.0, 500.0))));
+        assert_eq!(ruin.animation, Some(AnimationState::new(CITY_DESTROYED)));
+        assert!(ruin.city.is_none() && ruin.collider.is_none());
+        assert_eq!(
+            ruin.lifetime,
+            Some(Lifetime(animations.duration(CITY_DESTROYED)))
+        );
+    }
 }
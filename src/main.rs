@@ -1,20 +1,55 @@
 #![windows_subsystem = "windows"]
-use data::{Assets, Game};
-use draw::{draw_background, draw_game};
-use logic::{handle_resize, load_level, spawn_enemy_missiles, update_game};
+use animation::load_animations_from_file;
+use assist::AimAssist;
+use atlas::Atlas;
+use camera_fx::CameraFx;
+use canvas::Canvas;
+use cli::{Options, DEFAULT_RECORD_PATH};
+use data::{Assets, Game, DEFAULT_LEVEL_HEIGHT, DEFAULT_LEVEL_WIDTH};
+use draw::{draw_aim_assist, draw_background, draw_flash, draw_game, draw_hud, draw_playback};
+use ecs::World;
+use hud::{HighScore, Hud, HIGH_SCORE_PATH};
+use input::{pointer_presses, InputMap, InputSource, PlayerInput, INPUT_MAP_PATH};
+use logic::{handle_resize, load_level, load_level_from_file, start_wave, update_game};
 use macroquad::{
     audio::{self},
     prelude::*,
 };
 
+use palette::load_palettes_from_file;
+use particles::Particles;
+use replay::{Replay, ReplayInput};
+use rng::Rng;
 use std::{
+    process,
     rc::Rc,
     time::{SystemTime, UNIX_EPOCH},
 };
+use text::{load_font, load_strings};
+use ui::{options_action, OptionsAction, Playback};
 
+mod animation;
+mod arena;
+mod assist;
+mod atlas;
+mod camera_fx;
+mod canvas;
+mod cli;
+mod collision;
 mod data;
 mod draw;
+mod ecs;
+mod gamepad;
+mod grid;
+mod hud;
+mod input;
 mod logic;
+mod palette;
+mod particles;
+mod replay;
+mod rng;
+mod text;
+mod ui;
 
 fn window_conf() -> Conf {
     Conf {
@@ -27,57 +62,49 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    rand::srand(
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as u64,
-    );
-    let building1_texture = Texture2D::from_file_with_format(
-        &Assets::get("building_1.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    let building2_texture = Texture2D::from_file_with_format(
-        &Assets::get("building_2.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    let building3_texture = Texture2D::from_file_with_format(
-        &Assets::get("building_3.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    let plane_texture = Texture2D::from_file_with_format(
-        &Assets::get("plane.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    let missile_texture = Texture2D::from_file_with_format(
-        &Assets::get("missile.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    let replay = options.replay.as_deref().map(|path| {
+        Replay::load(path).unwrap_or_else(|error| {
+            eprintln!("could not load replay {}", error);
+            process::exit(1);
+        })
+    });
+    let level_data = load_level_from_file("level.json");
+    let seed = replay
+        .as_ref()
+        .map(|replay| replay.seed)
+        .or(options.seed)
+        .or(level_data.seed)
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        });
+
+    let atlas = Atlas::load();
+    let mut strings = load_strings();
+    if let Some(language) = &options.language {
+        if let Err(error) = strings.set_language(language) {
+            eprintln!("{}, using English", error);
+        }
+    }
+    let mut palettes = load_palettes_from_file("palettes.json");
+    if let Some(palette) = &options.palette {
+        if let Err(error) = palettes.set_preset(palette) {
+            eprintln!("{}, using {}", error, palettes.preset());
+        }
+    }
     let background_texture = Texture2D::from_file_with_format(
         &Assets::get("background.png").unwrap().data,
         Some(ImageFormat::Png),
     );
-    let cannon_base_texture = Texture2D::from_file_with_format(
-        &Assets::get("missile_launcher_part_1.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    let cannon_barrel_texture = Texture2D::from_file_with_format(
-        &Assets::get("missile_launcher_part_2.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    let ground_texture = Texture2D::from_file_with_format(
-        &Assets::get("ground.png").unwrap().data,
-        Some(ImageFormat::Png),
-    );
-    missile_texture.set_filter(FilterMode::Nearest);
-    plane_texture.set_filter(FilterMode::Nearest);
-    cannon_base_texture.set_filter(FilterMode::Nearest);
-    cannon_barrel_texture.set_filter(FilterMode::Nearest);
-    ground_texture.set_filter(FilterMode::Nearest);
-    building1_texture.set_filter(FilterMode::Nearest);
-    building2_texture.set_filter(FilterMode::Nearest);
-    building3_texture.set_filter(FilterMode::Nearest);
-    let building_textures = vec![building1_texture, building2_texture, building3_texture];
     let missile_fire_sound =
         audio::load_sound_from_bytes(&Assets::get("missile_fire.ogg").unwrap().data)
             .await
@@ -91,48 +118,144 @@ async fn main() {
             .unwrap();
 
     let mut game = Game {
-        buildings: vec![],
-        planes: vec![],
+        world: World::new(),
         enemy_missiles_spawnpoints: vec![],
         ground_entities: vec![],
-        enemy_missiles: vec![],
-        player_missiles: vec![],
-        cannons: vec![],
-        plane_texture: Rc::new(plane_texture),
-        building_textures: building_textures
-            .iter()
-            .map(|t| Rc::new(t.clone()))
-            .collect(),
-        missile_texture: Rc::new(missile_texture),
+        atlas: Rc::new(atlas),
         missile_fire_sound: Rc::new(missile_fire_sound),
-        cannon_base_texture: Rc::new(cannon_base_texture),
-        cannon_barrel_texture: Rc::new(cannon_barrel_texture),
-        ground_texture: Rc::new(ground_texture),
         explosion_sound: Rc::new(explosion_sound),
         enemy_missile_sound: Rc::new(enemy_missile_sound),
-        crosshairs: vec![],
+        animations: load_animations_from_file("animations.json"),
+        font: load_font(),
+        strings,
+        palettes,
+        aim_assist: options.aim_assist,
         score: 0,
         game_over: false,
-        camera: Camera2D {
-            zoom: vec2((1.0 / screen_width()) * 2.0, (1.0 / screen_height()) * 2.0),
-            target: vec2(screen_width() / 2.0, screen_height() / 2.0),
-            ..Default::default()
-        },
+        wave: 0,
+        wave_tick: 0,
+        tick: 0,
+        aim: Vec2::ZERO,
+        events: vec![],
+        particles: Particles::new(options.max_particles, seed),
+        launch_queue: vec![],
+        camera: Camera2D::default(),
+        level_size: vec2(DEFAULT_LEVEL_WIDTH, DEFAULT_LEVEL_HEIGHT),
+        screen_size: vec2(screen_width(), screen_height()),
+        seed,
+        rng: Rng::new(seed),
     };
 
-    load_level(&mut game);
-    spawn_enemy_missiles(&mut game);
+    load_level(&mut game, level_data);
+    start_wave(&mut game);
+    let canvas = Canvas::new(game.level_size);
 
-    set_camera(&game.camera);
+    // a live run is recorded, saved at game over or when F2 is pressed
+    let record_path = options
+        .record
+        .unwrap_or_else(|| DEFAULT_RECORD_PATH.to_string());
+    let mut recording = Replay::new(seed);
+    let mut recording_saved = false;
+    let mut high_score = HighScore::load(HIGH_SCORE_PATH).unwrap_or_else(|error| {
+        eprintln!("could not load high score {}", error);
+        HighScore::default()
+    });
+    let input_map = InputMap::load(INPUT_MAP_PATH).unwrap_or_else(|error| {
+        eprintln!("could not load input map {}, using the default one", error);
+        InputMap::default()
+    });
+    let mut player_input = PlayerInput::new(options.controls, input_map);
+    // the aim point is drawn instead, it does not always follow the mouse
+    show_mouse(false);
+    // taps are handled as touches, not as clicks too
+    simulate_mouse_with_touch(false);
+    let mut playback = Playback::new(replay.is_some());
+    let mut replay_input = replay.map(ReplayInput::new);
+    let mut camera_fx = CameraFx::new(options.camera_fx);
 
     loop {
-        let last_screen_width = screen_width();
-        let last_screen_height = screen_height();
-        handle_resize(last_screen_width, last_screen_height, &mut game);
+        handle_resize(&mut game);
+        let presses = pointer_presses(&game.camera);
+        let mut ticks = playback.ticks_this_frame(&presses, game.level_size);
+        if playback.options {
+            match options_action(&presses, game.level_size) {
+                Some(OptionsAction::NextLanguage) => game.strings.next_language(),
+                Some(OptionsAction::NextPalette) => game.palettes.next_preset(),
+                Some(OptionsAction::ToggleAimAssist) => game.aim_assist = !game.aim_assist,
+                Some(OptionsAction::Close) => playback.options = false,
+                None => {}
+            }
+        }
+        if replay_input.is_none() && !game.game_over {
+            player_input.collect(&game);
+            if !playback.takes_input(&presses, game.level_size) {
+                player_input.cancel_fires();
+            }
+        }
+        if !camera_fx.tick_this_frame() {
+            ticks = 0;
+        }
+        for _ in 0..ticks {
+            // the run ends with the last city, slow motion only plays out the effects
+            if game.game_over {
+                if !camera_fx.slow_motion() {
+                    break;
+                }
+                game.particles.update();
+                camera_fx.tick();
+                continue;
+            }
+            let input = match &mut replay_input {
+                Some(replay_input) => {
+                    if replay_input.finished(game.tick) {
+                        break;
+                    }
+                    replay_input.poll(&game)
+                }
+                None => {
+                    let input = player_input.poll(&game);
+                    recording.record(game.tick, &input);
+                    input
+                }
+            };
+            update_game(&mut game, &input);
+            let cities_left = game
+                .world
+                .values()
+                .filter(|entity| entity.city.is_some())
+                .count();
+            camera_fx.react(&game.events, cities_left);
+            camera_fx.tick();
+        }
+
+        let finished = game.game_over && !camera_fx.slow_motion();
+        let save_requested = (finished && !recording_saved) || is_key_pressed(KeyCode::F2);
+        if replay_input.is_none() && save_requested {
+            if let Err(error) = recording.save(&record_path) {
+                eprintln!("could not save replay {}", error);
+            }
+            recording_saved = finished;
+        }
+        // replays do not count, they only repeat a score that was already made
+        if replay_input.is_none() && finished && high_score.beat(game.score) {
+            if let Err(error) = high_score.save(HIGH_SCORE_PATH) {
+                eprintln!("could not save high score {}", error);
+            }
+        }
+
+        // shaking the canvas camera keeps the black bars still
+        set_camera(&camera_fx.shaken(&canvas.camera()));
         clear_background(BLACK);
-        draw_background(&background_texture);
-        update_game(&mut game);
+        let background_tint = game.palettes.for_wave(game.wave).background;
+        draw_background(&background_texture, game.level_size, background_tint);
         draw_game(&game);
+        if game.aim_assist {
+            draw_aim_assist(&game, &AimAssist::new(&game, player_input.controls));
+        }
+        draw_flash(game.level_size, camera_fx.flash());
+        draw_playback(&game, &playback);
+        canvas.present(&game.camera);
+        draw_hud(&game, &Hud::new(&game, &high_score));
         next_frame().await;
     }
 }
@@ -0,0 +1,169 @@
+use crate::data::Assets;
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_PALETTE_PRESET: &str = "arcade";
+
+/// What a colour is for. Entities keep this rather than a colour, which is
+/// looked up in the active palette when they are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteColor {
+    /// Drawn as the sprite was made, whatever the palette.
+    Plain,
+    Enemy,
+    Interceptor,
+    City,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Palette {
+    /// Multiplied with the background image.
+    #[serde(deserialize_with = "hex_color")]
+    pub background: Color,
+    /// Enemy missiles and their trails.
+    #[serde(deserialize_with = "hex_color")]
+    pub enemy: Color,
+    /// The player's missile trails and target markers.
+    #[serde(deserialize_with = "hex_color")]
+    pub interceptor: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub city: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub ground: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub hud: Color,
+    /// HUD text and gauges that need attention, like incoming missiles.
+    #[serde(deserialize_with = "hex_color")]
+    pub hud_warning: Color,
+}
+
+impl Palette {
+    pub fn color(&self, color: PaletteColor) -> Color {
+        match color {
+            PaletteColor::Plain => WHITE,
+            PaletteColor::Enemy => self.enemy,
+            PaletteColor::Interceptor => self.interceptor,
+            PaletteColor::City => self.city,
+        }
+    }
+}
+
+/// Reads colours written like `"#ff8000"`, or `"#ff800080"` with alpha.
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse_hex_color(&text)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid colour: {}", text)))
+}
+
+pub fn parse_hex_color(text: &str) -> Option<Color> {
+    let digits = text.strip_prefix('#')?;
+    let value = u32::from_str_radix(digits, 16).ok()?;
+    match digits.len() {
+        6 => Some(Color::from_hex(value)),
+        8 => {
+            let rgb = Color::from_hex(value >> 8);
+            Some(Color::new(
+                rgb.r,
+                rgb.g,
+                rgb.b,
+                (value & 0xff) as f32 / 255.0,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Palette presets, each a rotation of palettes that changes every few waves
+/// like the arcade game did.
+#[derive(Deserialize, Debug)]
+pub struct Palettes {
+    pub waves_per_palette: u32,
+    presets: BTreeMap<String, Vec<Palette>>,
+    #[serde(skip)]
+    preset: String,
+}
+
+impl Palettes {
+    pub fn preset(&self) -> &str {
+        &self.preset
+    }
+
+    pub fn set_preset(&mut self, name: &str) -> Result<(), String> {
+        if self.presets.get(name).is_none_or(Vec::is_empty) {
+            let names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+            return Err(format!(
+                "no palette preset {}, there is {}",
+                name,
+                names.join(", ")
+            ));
+        }
+        self.preset = name.to_string();
+        Ok(())
+    }
+
+    pub fn next_preset(&mut self) {
+        let next = self
+            .presets
+            .keys()
+            .skip_while(|name| **name != self.preset)
+            .nth(1)
+            .or_else(|| self.presets.keys().next());
+        if let Some(next) = next {
+            self.preset = next.clone();
+        }
+    }
+
+    /// Palette of the current preset for `wave`, counting from 1.
+    pub fn for_wave(&self, wave: u32) -> &Palette {
+        let rotation = &self.presets[&self.preset];
+        let index = (wave.max(1) - 1) / self.waves_per_palette.max(1);
+        &rotation[index as usize % rotation.len()]
+    }
+}
+
+pub fn load_palettes_from_file(path: &str) -> Palettes {
+    let data = Assets::get(path).unwrap().data;
+    let mut palettes: Palettes = serde_json::from_slice(&data).unwrap();
+    palettes.set_preset(DEFAULT_PALETTE_PRESET).unwrap();
+    palettes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(
+            parse_hex_color("#ff0000"),
+            Some(Color::new(1.0, 0.0, 0.0, 1.0))
+        );
+        let half = parse_hex_color("#00ff0080").unwrap();
+        assert_eq!((half.g, half.a), (1.0, 128.0 / 255.0));
+        assert_eq!(parse_hex_color("ff0000"), None);
+        assert_eq!(parse_hex_color("#ff00"), None);
+        assert_eq!(parse_hex_color("#gg0000"), None);
+    }
+
+    #[test]
+    fn palettes_rotate_every_few_waves() {
+        let mut palettes = load_palettes_from_file("palettes.json");
+        let waves = palettes.waves_per_palette;
+        let rotation = palettes.presets[DEFAULT_PALETTE_PRESET].len() as u32;
+        assert!(rotation > 1);
+        assert_eq!(palettes.for_wave(0), palettes.for_wave(1));
+        assert_eq!(palettes.for_wave(1), palettes.for_wave(waves));
+        assert_ne!(palettes.for_wave(waves), palettes.for_wave(waves + 1));
+        assert_eq!(
+            palettes.for_wave(1),
+            palettes.for_wave(waves * rotation + 1)
+        );
+
+        palettes.set_preset("colour_blind").unwrap();
+        let safe = palettes.for_wave(1);
+        assert_ne!(safe.enemy, safe.interceptor);
+        assert!(palettes.set_preset("missing").is_err());
+        assert_eq!(palettes.preset(), "colour_blind");
+    }
+}
@@ -0,0 +1,246 @@
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use std::f32::consts::PI;
+
+/// New particles are dropped while the pool is full.
+pub const DEFAULT_MAX_PARTICLES: usize = 2000;
+/// Ticks between two puffs of smoke behind a missile.
+pub const SMOKE_INTERVAL: u32 = 4;
+
+/// How the particles of an emitter look and move. Ranges are `(min, max)`,
+/// speeds in level units and times in ticks.
+pub struct ParticleStyle {
+    /// Particles emitted every tick the emitter is alive.
+    pub count: u32,
+    pub speed: (f32, f32),
+    pub lifetime: (u32, u32),
+    pub size: (f32, f32),
+    /// Colours from birth to death, blended in between.
+    pub ramp: &'static [Color],
+    /// Added to the vertical speed every tick, positive pulls down.
+    pub gravity: f32,
+    /// Angle particles are thrown at, and how far around it they spread.
+    pub direction: f32,
+    pub spread: f32,
+}
+
+pub const FIREBALL: ParticleStyle = ParticleStyle {
+    count: 12,
+    speed: (0.2, 1.2),
+    lifetime: (15, 30),
+    size: (3.0, 6.0),
+    ramp: &[
+        Color::new(1.0, 1.0, 0.8, 1.0),
+        Color::new(1.0, 0.6, 0.1, 0.9),
+        Color::new(0.6, 0.1, 0.0, 0.6),
+        Color::new(0.2, 0.2, 0.2, 0.0),
+    ],
+    gravity: -0.01,
+    direction: 0.0,
+    spread: PI,
+};
+
+pub const SPARKS: ParticleStyle = ParticleStyle {
+    count: 10,
+    speed: (1.5, 3.5),
+    lifetime: (10, 20),
+    size: (1.0, 1.5),
+    ramp: &[
+        Color::new(1.0, 1.0, 0.6, 1.0),
+        Color::new(1.0, 0.5, 0.0, 0.0),
+    ],
+    gravity: 0.08,
+    direction: 0.0,
+    spread: PI,
+};
+
+pub const SMOKE: ParticleStyle = ParticleStyle {
+    count: 1,
+    speed: (0.0, 0.15),
+    lifetime: (30, 50),
+    size: (1.5, 3.0),
+    ramp: &[
+        Color::new(0.7, 0.7, 0.7, 0.5),
+        Color::new(0.4, 0.4, 0.4, 0.0),
+    ],
+    gravity: -0.005,
+    direction: 0.0,
+    spread: PI,
+};
+
+pub const DEBRIS: ParticleStyle = ParticleStyle {
+    count: 6,
+    speed: (1.0, 2.5),
+    lifetime: (40, 60),
+    size: (2.0, 4.0),
+    ramp: &[
+        Color::new(0.5, 0.45, 0.4, 1.0),
+        Color::new(0.3, 0.25, 0.2, 1.0),
+        Color::new(0.2, 0.2, 0.2, 0.0),
+    ],
+    gravity: 0.1,
+    direction: -PI / 2.0,
+    spread: PI / 3.0,
+};
+
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub size: f32,
+    age: u32,
+    lifetime: u32,
+    ramp: &'static [Color],
+    gravity: f32,
+}
+
+impl Particle {
+    pub fn color(&self) -> Color {
+        let ramp = self.ramp;
+        if ramp.len() < 2 {
+            return ramp.first().copied().unwrap_or(WHITE);
+        }
+
+        let progress = self.age as f32 / self.lifetime as f32 * (ramp.len() - 1) as f32;
+        let index = (progress as usize).min(ramp.len() - 2);
+        let (from, to) = (ramp[index], ramp[index + 1]);
+        let t = progress - index as f32;
+        Color::new(
+            from.r + (to.r - from.r) * t,
+            from.g + (to.g - from.g) * t,
+            from.b + (to.b - from.b) * t,
+            from.a + (to.a - from.a) * t,
+        )
+    }
+}
+
+struct Emitter {
+    style: &'static ParticleStyle,
+    position: Vec2,
+    ticks_left: u32,
+}
+
+/// Every particle and emitter in the game. Purely visual: it draws from its
+/// own random numbers so that the particle cap never changes how a run plays.
+pub struct Particles {
+    particles: Vec<Particle>,
+    emitters: Vec<Emitter>,
+    max_particles: usize,
+    rng: Rng,
+}
+
+impl Particles {
+    pub fn new(max_particles: usize, seed: u64) -> Particles {
+        Particles {
+            particles: Vec::with_capacity(max_particles),
+            emitters: vec![],
+            max_particles,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn emit(&mut self, style: &'static ParticleStyle, position: Vec2, ticks: u32) {
+        if ticks == 0 {
+            return;
+        }
+        self.emitters.push(Emitter {
+            style,
+            position,
+            ticks_left: ticks,
+        });
+    }
+
+    pub fn update(&mut self) {
+        for index in 0..self.emitters.len() {
+            let Emitter {
+                style, position, ..
+            } = self.emitters[index];
+            for _ in 0..style.count {
+                self.spawn(style, position);
+            }
+            self.emitters[index].ticks_left -= 1;
+        }
+        self.emitters.retain(|emitter| emitter.ticks_left > 0);
+
+        for particle in &mut self.particles {
+            particle.velocity.y += particle.gravity;
+            particle.position += particle.velocity;
+            particle.age += 1;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    fn spawn(&mut self, style: &'static ParticleStyle, position: Vec2) {
+        if self.particles.len() >= self.max_particles {
+            return;
+        }
+
+        let rng = &mut self.rng;
+        let angle = style.direction + rng.gen_range_f32(-style.spread, style.spread);
+        let speed = rng.gen_range_f32(style.speed.0, style.speed.1);
+        let lifetime = rng.gen_range_usize(style.lifetime.0 as usize, style.lifetime.1 as usize);
+        self.particles.push(Particle {
+            position,
+            velocity: Vec2::from_angle(angle) * speed,
+            size: rng.gen_range_f32(style.size.0, style.size.1),
+            age: 0,
+            lifetime: lifetime.max(1) as u32,
+            ramp: style.ramp,
+            gravity: style.gravity,
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particles_stay_under_the_cap_and_die_of_age() {
+        let mut particles = Particles::new(50, 1);
+        for _ in 0..10 {
+            particles.emit(&FIREBALL, vec2(100.0, 100.0), 3);
+        }
+        particles.update();
+        assert_eq!(particles.iter().count(), 50);
+
+        for _ in 0..FIREBALL.lifetime.1 + 3 {
+            particles.update();
+        }
+        assert_eq!(particles.iter().count(), 0);
+
+        let mut disabled = Particles::new(0, 1);
+        disabled.emit(&SPARKS, Vec2::ZERO, 1);
+        disabled.update();
+        assert_eq!(disabled.iter().count(), 0);
+    }
+
+    #[test]
+    fn debris_falls_and_fades_along_its_ramp() {
+        let mut particles = Particles::new(100, 2);
+        particles.emit(&DEBRIS, vec2(0.0, 0.0), 1);
+        particles.update();
+        let first = particles.iter().next().unwrap();
+        let (start, start_color) = (first.velocity.y, first.color());
+        assert!(start < 0.0, "debris is thrown upwards");
+
+        for _ in 0..30 {
+            particles.update();
+        }
+        let first = particles.iter().next().unwrap();
+        assert!(first.velocity.y > start);
+        assert!(first.color().r < start_color.r);
+    }
+
+    #[test]
+    fn emitters_without_ticks_emit_nothing() {
+        let mut particles = Particles::new(100, 3);
+        particles.emit(&SPARKS, Vec2::ZERO, 0);
+        particles.update();
+        assert_eq!(particles.iter().count(), 0);
+    }
+}
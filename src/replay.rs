@@ -0,0 +1,151 @@
+use crate::data::Game;
+use crate::input::{Fire, InputSource, TickInput};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FireRecord {
+    pub tick: u32,
+    pub launcher: usize,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// The seed plus every shot of a run, which is all it takes to play the run
+/// back. Aiming between shots only turns the barrels, so it is not kept.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    /// Length of the run.
+    pub ticks: u32,
+    pub fires: Vec<FireRecord>,
+}
+
+impl Replay {
+    pub fn new(seed: u64) -> Replay {
+        Replay {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, tick: u32, input: &TickInput) {
+        for fire in &input.fires {
+            self.fires.push(FireRecord {
+                tick,
+                launcher: fire.launcher,
+                x: fire.aim.x,
+                y: fire.aim.y,
+            });
+        }
+        self.ticks = tick + 1;
+    }
+
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let data = fs::read(path).map_err(|error| format!("{}: {}", path, error))?;
+        serde_json::from_slice(&data).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let data = serde_json::to_vec(self).map_err(|error| error.to_string())?;
+        fs::write(path, data).map_err(|error| format!("{}: {}", path, error))
+    }
+}
+
+pub struct ReplayInput {
+    replay: Replay,
+    next: usize,
+    aim: Vec2,
+}
+
+impl ReplayInput {
+    pub fn new(replay: Replay) -> ReplayInput {
+        ReplayInput {
+            replay,
+            next: 0,
+            aim: Vec2::ZERO,
+        }
+    }
+
+    pub fn finished(&self, tick: u32) -> bool {
+        tick >= self.replay.ticks
+    }
+
+    /// Input recorded for `tick`. Ticks have to be asked for in order.
+    pub fn input_at(&mut self, tick: u32) -> TickInput {
+        let mut fires = vec![];
+        while let Some(record) = self.replay.fires.get(self.next) {
+            if record.tick != tick {
+                break;
+            }
+            self.next += 1;
+            self.aim = vec2(record.x, record.y);
+            fires.push(Fire {
+                launcher: record.launcher,
+                aim: self.aim,
+            });
+        }
+
+        TickInput {
+            aim: self.aim,
+            fires,
+        }
+    }
+}
+
+impl InputSource for ReplayInput {
+    fn poll(&mut self, game: &Game) -> TickInput {
+        self.input_at(game.tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_shots_play_back_on_the_same_ticks() {
+        let mut replay = Replay::new(99);
+        let shots = [
+            (3, 0, vec2(10.0, 20.5)),
+            (4, 2, vec2(0.1, 300.0)),
+            (4, 1, vec2(0.1, 300.0)),
+        ];
+        for tick in 0..10 {
+            let fires = shots
+                .iter()
+                .filter(|shot| shot.0 == tick)
+                .map(|&(_, launcher, aim)| Fire { launcher, aim })
+                .collect();
+            replay.record(
+                tick,
+                &TickInput {
+                    aim: vec2(tick as f32, 0.0),
+                    fires,
+                },
+            );
+        }
+
+        let data = serde_json::to_vec(&replay).unwrap();
+        let loaded: Replay = serde_json::from_slice(&data).unwrap();
+        assert_eq!(loaded, replay);
+
+        let mut input = ReplayInput::new(loaded);
+        let fires: Vec<_> = (0..10)
+            .flat_map(|tick| {
+                let fires = input.input_at(tick).fires;
+                fires.into_iter().map(move |fire| (tick, fire))
+            })
+            .collect();
+        assert_eq!(
+            fires,
+            shots
+                .iter()
+                .map(|&(tick, launcher, aim)| (tick, Fire { launcher, aim }))
+                .collect::<Vec<_>>()
+        );
+        assert!(!input.finished(9));
+        assert!(input.finished(10));
+    }
+}
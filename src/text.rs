@@ -0,0 +1,74 @@
+use crate::data::Assets;
+use macroquad::prelude::*;
+use std::collections::BTreeMap;
+use string_tables::{Strings, Table};
+
+pub const FONT_PATH: &str = "fonts/CommanderPixel.ttf";
+const LANGUAGES_FOLDER: &str = "lang/";
+
+/// Every language in the assets, by language code.
+fn language_tables() -> BTreeMap<String, Table> {
+    let mut tables = BTreeMap::new();
+    for file in Assets::iter() {
+        let Some(code) = file
+            .strip_prefix(LANGUAGES_FOLDER)
+            .and_then(|name| name.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let data = Assets::get(&file).unwrap().data;
+        let table =
+            serde_json::from_slice(&data).unwrap_or_else(|error| panic!("{}: {}", file, error));
+        tables.insert(code.to_string(), table);
+    }
+    tables
+}
+
+/// Starts in English.
+pub fn load_strings() -> Strings {
+    Strings::new(language_tables())
+}
+
+pub fn load_font() -> Font {
+    let mut font = load_ttf_font_from_bytes(&Assets::get(FONT_PATH).unwrap().data).unwrap();
+    // keeps the pixels square when the camera scales the text up
+    font.set_filter(FilterMode::Nearest);
+    font
+}
+
+/// Draws `text` with the game's font, `y` being the baseline like
+/// `draw_text`.
+pub fn draw_ui_text(font: &Font, text: &str, x: f32, y: f32, font_size: f32, color: Color) {
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font: Some(font),
+            font_size: font_size as u16,
+            color,
+            ..Default::default()
+        },
+    );
+}
+
+pub fn measure_ui_text(font: &Font, text: &str, font_size: f32) -> TextDimensions {
+    measure_text(text, Some(font), font_size as u16, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use string_tables::FALLBACK_LANGUAGE;
+
+    #[test]
+    fn every_language_has_every_english_string() {
+        let tables = language_tables();
+        let english = &tables[FALLBACK_LANGUAGE];
+        for (code, table) in &tables {
+            for key in english.keys() {
+                assert!(table.contains_key(key), "{} is missing {}", code, key);
+            }
+        }
+    }
+}
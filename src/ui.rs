@@ -0,0 +1,226 @@
+use macroquad::prelude::*;
+
+/// Buttons are at least this big, in level units, so they can be tapped.
+pub const BUTTON_SIZE: f32 = 64.0;
+const BUTTON_GAP: f32 = 8.0;
+
+/// Ticks run per frame while fast-forwarding a replay.
+pub const FAST_FORWARD_SPEED: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Button {
+    pub rect: Rect,
+    pub label: &'static str,
+    /// Drawn highlighted, for buttons that toggle something on.
+    pub active: bool,
+}
+
+impl Button {
+    pub fn pressed(&self, presses: &[Vec2]) -> bool {
+        presses.iter().any(|press| self.rect.contains(*press))
+    }
+}
+
+/// Lays buttons out in a row along the top right corner of the level, the
+/// first one rightmost.
+pub fn toolbar(level_size: Vec2, buttons: &[(&'static str, bool)]) -> Vec<Button> {
+    buttons
+        .iter()
+        .enumerate()
+        .map(|(index, &(label, active))| Button {
+            rect: Rect::new(
+                level_size.x - (BUTTON_SIZE + BUTTON_GAP) * (index + 1) as f32,
+                30.0,
+                BUTTON_SIZE,
+                BUTTON_SIZE,
+            ),
+            label,
+            active,
+        })
+        .collect()
+}
+
+/// What a press in the options menu asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionsAction {
+    NextLanguage,
+    NextPalette,
+    ToggleAimAssist,
+    Close,
+}
+
+/// Rows of the options menu: the language, colours, aim assist, then back.
+pub fn options_rows(level_size: Vec2) -> [Rect; 4] {
+    let (width, height) = (level_size.x * 0.5, BUTTON_SIZE);
+    let x = (level_size.x - width) / 2.0;
+    let top = level_size.y * 0.4;
+    [0.0, 1.0, 2.0, 3.0].map(|row| Rect::new(x, top + (height + BUTTON_GAP) * row, width, height))
+}
+
+/// Which options menu row `presses` hit, if any.
+pub fn options_row_pressed(presses: &[Vec2], level_size: Vec2) -> Option<OptionsAction> {
+    let actions = [
+        OptionsAction::NextLanguage,
+        OptionsAction::NextPalette,
+        OptionsAction::ToggleAimAssist,
+        OptionsAction::Close,
+    ];
+    options_rows(level_size)
+        .into_iter()
+        .zip(actions)
+        .find(|(row, _)| presses.iter().any(|press| row.contains(*press)))
+        .map(|(_, action)| action)
+}
+
+/// Left, right or Enter switch the language, C the colours and A the aim
+/// assist, otherwise a press on a row.
+pub fn options_action(presses: &[Vec2], level_size: Vec2) -> Option<OptionsAction> {
+    let switch_keys = [KeyCode::Left, KeyCode::Right, KeyCode::Enter];
+    if switch_keys.iter().any(|key| is_key_pressed(*key)) {
+        return Some(OptionsAction::NextLanguage);
+    }
+    if is_key_pressed(KeyCode::C) {
+        return Some(OptionsAction::NextPalette);
+    }
+    if is_key_pressed(KeyCode::A) {
+        return Some(OptionsAction::ToggleAimAssist);
+    }
+    options_row_pressed(presses, level_size)
+}
+
+#[derive(Debug, Default)]
+pub struct Playback {
+    pub replay: bool,
+    pub paused: bool,
+    /// The options menu is open, which holds the game like a pause.
+    pub options: bool,
+    /// Toggled by the button, holding F fast-forwards too.
+    pub fast_forward: bool,
+    holding_fast_forward: bool,
+}
+
+impl Playback {
+    pub fn new(replay: bool) -> Playback {
+        Playback {
+            replay,
+            ..Default::default()
+        }
+    }
+
+    pub fn fast_forwarding(&self) -> bool {
+        self.fast_forward || self.holding_fast_forward
+    }
+
+    pub fn buttons(&self, level_size: Vec2) -> Vec<Button> {
+        let mut buttons = vec![("II", self.paused), ("...", self.options)];
+        if self.replay {
+            buttons.push(("|>", false));
+            buttons.push((">>", self.fast_forward));
+        }
+        toolbar(level_size, &buttons)
+    }
+
+    /// Whether shots can be fired: the game is not held, and none of
+    /// `presses` went to a button.
+    pub fn takes_input(&self, presses: &[Vec2], level_size: Vec2) -> bool {
+        !self.paused
+            && !self.options
+            && !self
+                .buttons(level_size)
+                .iter()
+                .any(|button| button.pressed(presses))
+    }
+
+    /// `presses` are clicks and taps in level coordinates.
+    pub fn ticks_this_frame(&mut self, presses: &[Vec2], level_size: Vec2) -> u32 {
+        let buttons = self.buttons(level_size);
+        let pressed = |index: usize| buttons.get(index).is_some_and(|b| b.pressed(presses));
+        let (pause, options, step, fast_forward) = (pressed(0), pressed(1), pressed(2), pressed(3));
+
+        if is_key_pressed(KeyCode::P) || pause {
+            self.paused = !self.paused;
+        }
+        if is_key_pressed(KeyCode::Escape) || options {
+            self.options = !self.options;
+        }
+        if fast_forward {
+            self.fast_forward = !self.fast_forward;
+        }
+        self.holding_fast_forward = self.replay && is_key_down(KeyCode::F);
+        let step = self.replay && (step || is_key_pressed(KeyCode::Period));
+
+        if self.options {
+            0
+        } else if self.paused {
+            step as u32
+        } else if pause || options || fast_forward || step {
+            0
+        } else if self.fast_forwarding() {
+            FAST_FORWARD_SPEED
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toolbar_buttons_are_tappable_and_do_not_overlap() {
+        let level_size = vec2(800.0, 600.0);
+        let buttons = toolbar(level_size, &[("II", false), ("|>", false), (">>", true)]);
+        for button in &buttons {
+            assert!(button.rect.w >= BUTTON_SIZE && button.rect.h >= BUTTON_SIZE);
+            assert!(button.rect.right() <= level_size.x);
+        }
+        for pair in buttons.windows(2) {
+            assert!(!pair[0].rect.overlaps(&pair[1].rect));
+        }
+
+        let center = buttons[1].rect.center();
+        assert!(buttons[1].pressed(&[vec2(0.0, 0.0), center]));
+        assert!(!buttons[0].pressed(&[center]));
+    }
+
+    #[test]
+    fn presses_on_buttons_or_while_held_do_not_fire() {
+        let level_size = vec2(800.0, 600.0);
+        let mut playback = Playback::new(false);
+        let pause = playback.buttons(level_size)[0].rect.center();
+        assert!(playback.takes_input(&[vec2(100.0, 300.0)], level_size));
+        assert!(!playback.takes_input(&[pause], level_size));
+
+        playback.paused = true;
+        assert!(!playback.takes_input(&[vec2(100.0, 300.0)], level_size));
+    }
+
+    #[test]
+    fn options_rows_are_tappable() {
+        let level_size = vec2(800.0, 600.0);
+        let rows = options_rows(level_size);
+        let [language, palette, aim_assist, back] = rows;
+        for pair in rows.windows(2) {
+            assert!(pair[0].h >= BUTTON_SIZE && !pair[0].overlaps(&pair[1]));
+        }
+        assert!(back.bottom() <= level_size.y);
+        assert_eq!(
+            options_row_pressed(&[language.center()], level_size),
+            Some(OptionsAction::NextLanguage)
+        );
+        assert_eq!(
+            options_row_pressed(&[palette.center()], level_size),
+            Some(OptionsAction::NextPalette)
+        );
+        assert_eq!(
+            options_row_pressed(&[aim_assist.center()], level_size),
+            Some(OptionsAction::ToggleAimAssist)
+        );
+        assert_eq!(
+            options_row_pressed(&[back.center()], level_size),
+            Some(OptionsAction::Close)
+        );
+        assert_eq!(options_row_pressed(&[Vec2::ZERO], level_size), None);
+    }
+}
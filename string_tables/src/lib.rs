@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+pub const FALLBACK_LANGUAGE: &str = "en";
+const LANGUAGE_NAME_KEY: &str = "language_name";
+
+pub type Table = HashMap<String, String>;
+
+/// UI text of the game and the level editor, by language code and key.
+/// Strings can have `{0}`, `{1}`... placeholders for values.
+#[derive(Debug, Default)]
+pub struct Strings {
+    tables: BTreeMap<String, Table>,
+    language: String,
+}
+
+impl Strings {
+    pub fn new(tables: BTreeMap<String, Table>) -> Strings {
+        Strings {
+            tables,
+            language: FALLBACK_LANGUAGE.to_string(),
+        }
+    }
+
+    pub fn set_language(&mut self, code: &str) -> Result<(), String> {
+        if !self.tables.contains_key(code) {
+            let languages: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+            return Err(format!(
+                "no language {}, there is {}",
+                code,
+                languages.join(", ")
+            ));
+        }
+        self.language = code.to_string();
+        Ok(())
+    }
+
+    pub fn next_language(&mut self) {
+        let next = self
+            .tables
+            .keys()
+            .skip_while(|code| **code != self.language)
+            .nth(1)
+            .or_else(|| self.tables.keys().next());
+        if let Some(next) = next {
+            self.language = next.clone();
+        }
+    }
+
+    /// Keys missing from English too show up as `[key]` so they are easy to
+    /// spot.
+    pub fn get(&self, key: &str) -> String {
+        [self.language.as_str(), FALLBACK_LANGUAGE]
+            .iter()
+            .find_map(|code| self.tables.get(*code)?.get(key))
+            .cloned()
+            .unwrap_or_else(|| format!("[{}]", key))
+    }
+
+    /// Placeholders are filled in a single pass, so arguments that contain
+    /// `{0}` themselves are left as they are.
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let template = self.get(key);
+        let mut text = String::with_capacity(template.len());
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let placeholder = rest.find('}').and_then(|end| {
+                let index: usize = rest[1..end].parse().ok()?;
+                Some((args.get(index)?, end))
+            });
+            match placeholder {
+                Some((arg, end)) => {
+                    text.push_str(&arg.to_string());
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    text.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+
+    /// Name of the current language, in that language.
+    pub fn language_name(&self) -> String {
+        self.get(LANGUAGE_NAME_KEY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, &str)]) -> Table {
+        entries
+            .iter()
+            .map(|&(key, text)| (key.to_string(), text.to_string()))
+            .collect()
+    }
+
+    fn strings() -> Strings {
+        let mut tables = BTreeMap::new();
+        tables.insert(
+            "en".to_string(),
+            table(&[
+                ("score", "Score: {0}"),
+                ("back", "Back"),
+                ("wave", "{0} {1}"),
+            ]),
+        );
+        tables.insert("pl".to_string(), table(&[("score", "Wynik: {0}")]));
+        Strings::new(tables)
+    }
+
+    #[test]
+    fn strings_fall_back_to_english_then_to_a_placeholder() {
+        let mut strings = strings();
+        assert_eq!(strings.format("score", &[&10]), "Score: 10");
+        assert_eq!(strings.format("wave", &[&1, &"x2"]), "1 x2");
+
+        strings.set_language("pl").unwrap();
+        assert_eq!(strings.format("score", &[&10]), "Wynik: 10");
+        assert_eq!(strings.get("back"), "Back");
+        assert_eq!(strings.get("missing"), "[missing]");
+        assert!(strings.set_language("xx").is_err());
+        assert_eq!(strings.language, "pl");
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_one_pass() {
+        let strings = strings();
+        assert_eq!(strings.format("wave", &[&"{1}", &"x2"]), "{1} x2");
+        assert_eq!(strings.format("wave", &[&"a"]), "a {1}");
+        assert_eq!(strings.format("score", &[]), "Score: {0}");
+    }
+
+    #[test]
+    fn next_language_cycles_through_all_of_them() {
+        let mut strings = strings();
+        strings.next_language();
+        assert_eq!(strings.language, "pl");
+        strings.next_language();
+        assert_eq!(strings.language, "en");
+    }
+}